@@ -1,4 +1,5 @@
 ﻿use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
 
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -37,7 +38,7 @@ pub struct MenuState {
     pub state: MenuStates,
 }
 
-#[derive(Component, PartialEq, Copy, Clone, Debug, Eq, Hash, Default)]
+#[derive(Component, PartialEq, Copy, Clone, Debug, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -65,15 +66,45 @@ pub fn position_to_translation(x: i32, y: i32, z: i32) -> Transform {
     Transform::from_xyz(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, z as f32 * TILE_SIZE)
 }
 
-#[derive(Component, Debug, Clone, PartialEq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TileType {
-    Grass, Dirt, Water, Wall, WallGame, Gravel, Void
+    Grass, Dirt, Water, Wall, WallGame, Gravel, Void,
+    // Bedrock below the soil layer
+    Stone, Granite,
+    // Underground
+    CopperOre, IronOre, GoldOre, Magma, CaveFloor,
+    // Z-level connectors
+    Ramp, StairUp, StairDown, Ladder,
 }
 
 impl TileType {
     pub fn is_wall(&self) -> bool {
         self.material_properties().is_obstacle()
     }
+    /// A ramp lets a unit walk onto a neighbouring tile one level up.
+    pub fn is_ramp(&self) -> bool {
+        *self == TileType::Ramp
+    }
+    /// Can climb from this tile to the tile directly above.
+    pub fn connects_up(&self) -> bool {
+        matches!(self, TileType::StairUp | TileType::Ladder)
+    }
+    /// Can climb from this tile to the tile directly below.
+    pub fn connects_down(&self) -> bool {
+        matches!(self, TileType::StairDown | TileType::Ladder)
+    }
+    pub fn is_connector(&self) -> bool {
+        self.is_ramp() || self.connects_up() || self.connects_down()
+    }
+    /// The loose chunk of ore found next to a vein of this tile, if any.
+    pub fn ore_item(&self) -> Option<ItemType> {
+        match self {
+            TileType::CopperOre => Some(ItemType::CopperOre),
+            TileType::IronOre => Some(ItemType::IronOre),
+            TileType::GoldOre => Some(ItemType::GoldOre),
+            _ => None,
+        }
+    }
     pub fn get_texture_coords(&self) -> (u32, u32) {
         match self {
             TileType::Grass => (0, 0),
@@ -82,6 +113,17 @@ impl TileType {
             TileType::Wall | TileType::WallGame => (3, 0),
             TileType::Gravel => (4, 0),
             TileType::Void => (0, 0),
+            TileType::Ramp => (5, 0),
+            TileType::StairUp => (6, 0),
+            TileType::StairDown => (7, 0),
+            TileType::Ladder => (8, 0),
+            TileType::Stone => (9, 0),
+            TileType::Granite => (10, 0),
+            TileType::CopperOre => (11, 0),
+            TileType::IronOre => (12, 0),
+            TileType::GoldOre => (13, 0),
+            TileType::Magma => (14, 0),
+            TileType::CaveFloor => (15, 0),
         }
     }
     pub fn material_properties(&self) -> MaterialProperties {
@@ -92,11 +134,21 @@ impl TileType {
             TileType::Wall | TileType::WallGame => MaterialProperties { hardness: 5.0, toughness: 10.0, energy_density: 0.0, mass: 10.0, conductivity: 0.1 },
             TileType::Gravel => MaterialProperties { hardness: 0.8, toughness: 0.3, energy_density: 0.0, mass: 1.8, conductivity: 0.6 },
             TileType::Void => MaterialProperties::default(),
+            TileType::Ramp => MaterialProperties { hardness: 0.3, toughness: 0.5, energy_density: 0.1, mass: 1.5, conductivity: 0.4 },
+            TileType::StairUp | TileType::StairDown => MaterialProperties { hardness: 3.0, toughness: 3.0, energy_density: 0.0, mass: 5.0, conductivity: 0.2 },
+            TileType::Ladder => MaterialProperties { hardness: 2.0, toughness: 2.0, energy_density: 5.0, mass: 2.0, conductivity: 0.2 },
+            TileType::Stone => MaterialProperties { hardness: 4.0, toughness: 8.0, energy_density: 0.0, mass: 12.0, conductivity: 0.3 },
+            TileType::Granite => MaterialProperties { hardness: 6.0, toughness: 12.0, energy_density: 0.0, mass: 14.0, conductivity: 0.25 },
+            TileType::CopperOre => MaterialProperties { hardness: 4.5, toughness: 9.0, energy_density: 0.0, mass: 13.0, conductivity: 0.9 },
+            TileType::IronOre => MaterialProperties { hardness: 6.5, toughness: 10.0, energy_density: 0.0, mass: 16.0, conductivity: 0.7 },
+            TileType::GoldOre => MaterialProperties { hardness: 3.5, toughness: 8.0, energy_density: 0.0, mass: 20.0, conductivity: 0.95 },
+            TileType::Magma => MaterialProperties { hardness: 0.0, toughness: 0.0, energy_density: 0.0, mass: 3.0, conductivity: 1.0 },
+            TileType::CaveFloor => MaterialProperties { hardness: 0.8, toughness: 0.3, energy_density: 0.0, mass: 1.8, conductivity: 0.4 },
         }
     }
 }
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EnvironmentalData {
     pub temperature: f32,
     pub humidity: f32,
@@ -115,6 +167,12 @@ pub enum VisualizationMode {
 #[derive(Component)]
 pub struct MapTile;
 
+/// Pulls the temperature of the tile it stands on towards `temperature`, like a fire or a stove.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct HeatSource {
+    pub temperature: f32,
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct SizeXYZ {
     pub width: f32,
@@ -131,7 +189,7 @@ impl SizeXYZ {
     }
 }
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MaterialProperties {
     pub mass: f32,
     pub hardness: f32,
@@ -145,30 +203,46 @@ impl MaterialProperties {
         // High toughness and mass block movement
         self.toughness > 4.0 && self.mass > 10.0
     }
+    /// Extra force a strike gets from holding this as a tool.
+    pub fn striking_bonus(&self) -> f32 {
+        (self.hardness * 0.5) + (self.mass * 0.1)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum AtomicAction {
     Move(Position),
     ApplyForce(Entity, f32), // Target, Force magnitude
     Consume(Entity),        // Absorb energy
     Link(Entity, Entity),   // Attach two entities
     Scan,                   // Perceive surroundings
+    Drink(Position),        // Drink from water next to the unit
+    Rest,                   // Sleep until rested
+    Attack(Entity),         // Chase a creature and strike it until it dies or gets away
+    Throw(Entity),          // Throw what the unit is holding at a creature
 }
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Genome {
     // Physical
     pub size: f32,
     pub mobility: f32,
     pub sensory_range: f32,
     pub physical_strength: f32,
+    #[serde(default)]
+    pub swimming: f32, // 0.0=sinks, 1.0=aquatic; 0.5 and up can cross deep water
     // Metabolic
     pub metabolic_efficiency: f32,
     pub diet_type: f32, // 0.0=photosynthetic, 1.0=carnivorous
     pub thermal_tolerance: f32,
     // Behavioral
     pub aggression: f32,
+    #[serde(default)]
+    pub nocturnal: f32, // 0.0=active by day, 1.0=active by night
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f32, // 0.0=never learns from experience, 1.0=only the last outcome counts
+    #[serde(default = "default_boldness")]
+    pub boldness: f32, // 0.0=flees any threat, 1.0=stands and fights
     pub sociality: f32,
     pub mutation_rate: f32,
     // Motivation Weights (0.0 to 1.0)
@@ -178,6 +252,19 @@ pub struct Genome {
 }
 
 impl Genome {
+    pub fn can_swim(&self) -> bool {
+        self.swimming >= 0.5
+    }
+    /// How much this creature wants to be up and about at the given light level, 0..1.
+    /// Below 0.5 it is in its resting window.
+    pub fn activity(&self, daylight: f32) -> f32 {
+        daylight * (1.0 - self.nocturnal) + (1.0 - daylight) * self.nocturnal
+    }
+    /// How well this creature remembers what it has seen, from its senses: 1.0 for a
+    /// sensory range of 15 tiles. Scales how many memories it keeps and how long they last.
+    pub fn intelligence(&self) -> f32 {
+        (self.sensory_range / 15.0).clamp(0.2, 3.0)
+    }
     pub fn genetic_distance(&self, other: &Genome) -> f32 {
         let d = (self.size - other.size).powi(2) +
                 (self.mobility - other.mobility).powi(4) +
@@ -186,24 +273,35 @@ impl Genome {
                 (self.thermal_tolerance - other.thermal_tolerance).powi(2) +
                 (self.physical_strength - other.physical_strength).powi(2) +
                 (self.aggression - other.aggression).powi(2) +
+                (self.nocturnal - other.nocturnal).powi(2) +
+                (self.learning_rate - other.learning_rate).powi(2) +
+                (self.boldness - other.boldness).powi(2) +
                 (self.weight_hunger - other.weight_hunger).powi(2) +
                 (self.weight_fatigue - other.weight_fatigue).powi(2);
         d.sqrt()
     }
 }
 
-#[derive(Component, Debug, Clone, Default)]
+fn default_learning_rate() -> f32 {
+    0.3
+}
+
+fn default_boldness() -> f32 {
+    0.5
+}
+
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Generation {
     pub value: u32,
 }
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReproductionStatus {
     pub energy_threshold: f32,
     pub last_reproduction_tick: u64,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Need {
     pub current: f32,
     pub max: f32,
@@ -213,7 +311,7 @@ pub struct Need {
     pub high: f32,
 }
 
-#[derive(Component, Default, Clone)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct PhysicalBody {
     pub energy_storage: f32,
     pub energy_max: f32,
@@ -221,6 +319,8 @@ pub struct PhysicalBody {
     pub needs_food: Option<Need>,
     pub needs_sleep: Option<Need>,
     pub needs_entertainment: Option<Need>,
+    #[serde(default)]
+    pub needs_water: Option<Need>,
     pub index: u32,
     pub crisis: Option<String>,
     pub danger: Option<Danger>,
@@ -242,13 +342,16 @@ impl PhysicalBody {
         if let Some(need) = &self.needs_entertainment {
             info.push(format!("Boredom: {:.0}%", (1.0 - need.current / need.max) * 100.0));
         }
+        if let Some(need) = &self.needs_water {
+            info.push(format!("Thirst: {:.0}%", (1.0 - need.current / need.max) * 100.0));
+        }
         info
     }
     pub fn info_panel_attributes(&self) -> Vec<String> { vec![] }
     pub fn info_panel_skills(&self) -> Vec<String> { vec![] }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Affliction {
     pub location: AfflictionLocation,
     pub affliction_type: AfflictionType,
@@ -257,7 +360,7 @@ pub struct Affliction {
     pub worsening: bool,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Skill {
     pub experience: i32,
     pub exp_lost: i32,
@@ -269,7 +372,7 @@ impl Skill {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Skillset {
     pub animal_raising: Skill,
     pub brawling: Skill,
@@ -286,7 +389,7 @@ pub struct Skillset {
     pub woodcutting: Skill,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Attributeset {
     pub strength: i32,
     pub dexterity: i32,
@@ -296,7 +399,7 @@ pub struct Attributeset {
     pub charisma: i32,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Brain {
     pub task: Option<Task>,
     pub task_queue: Vec<Task>,
@@ -306,6 +409,10 @@ pub struct Brain {
     pub memory: Vec<Memory>,
     pub motivation: Option<Motivation>,
     pub order: Option<String>,
+    #[serde(default)]
+    pub last_scan: Option<u64>,
+    #[serde(default)]
+    pub episode: Option<Episode>,
 }
 
 impl Brain {
@@ -320,6 +427,13 @@ impl Brain {
         if let Some(motivation) = self.motivation {
             info.push(format!("Motivation: {:?}", motivation));
         }
+        let lessons = self.memory.iter().filter(|m| matches!(m, Memory::Learned { .. })).count();
+        if self.memory.len() > lessons {
+            info.push(format!("Remembers: {} things", self.memory.len() - lessons));
+        }
+        if lessons > 0 {
+            info.push(format!("Learned: {} lessons", lessons));
+        }
         info
     }
     pub fn remotivate(&mut self) {
@@ -327,7 +441,6 @@ impl Brain {
         self.task_queue.clear();
         self.motivation = None;
     }
-    pub fn get_next_personality_trait(&self) -> Option<PersonalityTrait> { None }
 }
 
 #[derive(Component, Default)]
@@ -341,24 +454,72 @@ pub struct Pathing {
 #[derive(Component, Copy, Clone, Debug, PartialEq)]
 pub struct Targeting {
     pub target: Entity,
+    pub last_strike: u64,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Memory {
-    Seen(Entity, Position),
+    /// Something noticed by a scan: where it was, on which tick, and what it is made of.
+    Seen { entity: Entity, position: Position, tick: u64, material: MaterialProperties, item: Option<ItemType> },
+    /// What a sequence of actions on a kind of material has paid off, on average: energy and
+    /// health gained, as a share of the maximum.
+    Learned { actions: Vec<ActionKind>, target: MaterialFeatures, value: f32, trials: u32 },
+    /// Drinkable water noticed by a scan, and on which tick.
+    Water { position: Position, tick: u64 },
+}
+
+/// An atomic action without its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ActionKind {
+    Move, ApplyForce, Consume, Link, Scan, Drink, Rest, Attack, Throw,
+}
+
+impl From<&AtomicAction> for ActionKind {
+    fn from(action: &AtomicAction) -> Self {
+        match action {
+            AtomicAction::Move(_) => ActionKind::Move,
+            AtomicAction::ApplyForce(..) => ActionKind::ApplyForce,
+            AtomicAction::Consume(_) => ActionKind::Consume,
+            AtomicAction::Link(..) => ActionKind::Link,
+            AtomicAction::Scan => ActionKind::Scan,
+            AtomicAction::Drink(_) => ActionKind::Drink,
+            AtomicAction::Rest => ActionKind::Rest,
+            AtomicAction::Attack(_) => ActionKind::Attack,
+            AtomicAction::Throw(_) => ActionKind::Throw,
+        }
+    }
 }
 
-#[derive(Component, Debug, Clone, Copy, PartialEq)]
-pub enum PersonalityType {
-    Human, Vicious, None, Territorial
+/// A material reduced to coarse bands (0..=3 each), so experiences with similar things count together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MaterialFeatures {
+    pub energy: u8,
+    pub hardness: u8,
+    pub toughness: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+impl MaterialFeatures {
+    pub fn of(material: &MaterialProperties) -> Self {
+        let band = |x: f32| if x < 0.1 { 0 } else if x < 1.0 { 1 } else if x < 10.0 { 2 } else { 3 };
+        Self { energy: band(material.energy_density), hardness: band(material.hardness), toughness: band(material.toughness) }
+    }
+}
+
+/// An action sequence under way, with the unit's state when it started, so its outcome can be learned from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Episode {
+    pub actions: Vec<ActionKind>,
+    pub target: MaterialFeatures,
+    pub energy: f32,
+    pub health: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PersonalityTrait {
     Human, Vicious, None, Territorial, Creature
 }
 
-#[derive(Component, PartialEq, Copy, Clone, Debug, Default)]
+#[derive(Component, PartialEq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum Task {
     #[default]
     Idle,
@@ -367,6 +528,8 @@ pub enum Task {
     Social,      // Interaction/Play
     Meander,     // Random exploration
     Manual,      // User-assigned (legacy placeholder)
+    Fight,       // In melee with a threat or rival
+    Hunt,        // Running down prey
 }
 
 impl Task {
@@ -375,13 +538,14 @@ impl Task {
     }
 }
 
-#[derive(Component, PartialEq, Copy, Clone, Debug)]
+#[derive(Component, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Motivation {
     Hunger,
     Thirst,
     Tired,
     Bored,
     Fear,
+    Rage,
     Idle,
 }
 
@@ -390,7 +554,7 @@ pub enum ForageType {
     Once, Repeat
 }
 
-#[derive(Component, Debug, Clone, Copy, PartialEq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ActorType {
     Man, Woman, Elf, Dwarf, Spider, Rat, Cyclops, Monster, Crab
 }
@@ -458,11 +622,18 @@ pub struct Selected;
 #[derive(Component)]
 pub struct WorkMarker;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Nest {
     pub position: Position,
 }
 
+/// Marks a unit sleeping through `AtomicAction::Rest`: it restores its sleep need, burns less
+/// energy, and takes harder blows. Any loss of health from when it dozed off wakes it.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Asleep {
+    pub health: f32,
+}
+
 #[derive(Component)]
 pub struct Attackable;
 
@@ -471,30 +642,30 @@ pub struct StatusDisplay {
     pub text: String,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum AfflictionLocation {
     #[default]
     Head, LeftArm, RightArm, LeftLeg, RightLeg, Torso, Bladder, Intestines, Genitals, Heart, Lungs, Brain, Stomach, Liver, Spleen, Kidneys
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum AfflictionType {
     #[default]
     Pain, Inflammation, Disease, Wound, BrokenBone, Cut, Frostbite, Infection
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Danger {
     pub danger_type: DangerType,
     pub danger_source: Option<Entity>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum DangerType {
     Fire, Predator, Enemy, NaturalDisaster, Attacked
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct HasName {
     pub name: String,
 }
@@ -502,7 +673,7 @@ pub struct HasName {
 #[derive(Component)]
 pub struct ClickedOn;
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Plant {
     pub growth: f32,
     pub plant_type: ItemType,
@@ -545,6 +716,24 @@ pub struct VisualFeedback {
 #[derive(Component, Default)]
 pub struct Dying;
 
+/// The remains of a creature, drawn as what it was.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Corpse {
+    pub actor_type: ActorType,
+}
+
+/// A thrown thing in flight. A throw that will hit follows its target; a miss flies on to
+/// where it was aimed, and either comes down there as an ordinary item.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Projectile {
+    pub thrower: Entity,
+    pub target: Entity,
+    pub destination: Position,
+    pub hit: bool,
+    /// The last ground it flew over, where it drops if nothing below it will hold it.
+    pub last_ground: Position,
+}
+
 #[derive(Component)]
 pub struct GeneratedBy { pub entity: Entity }
 
@@ -554,7 +743,7 @@ pub struct MoveRandom;
 #[derive(Component)]
 pub struct MoveTowardsNearestAttackable;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Food {
     pub spoilage: f32,
     pub spoilage_rate: f32,
@@ -572,6 +761,12 @@ pub struct PauseOverlay;
 #[derive(Component)]
 pub struct MainMenuOverlay;
 
+#[derive(Component)]
+pub struct MainMenuButtonStartGame;
+
+#[derive(Component)]
+pub struct MainMenuButtonLoadGame;
+
 #[derive(Component)]
 pub struct TextName;
 
@@ -1,8 +1,4 @@
 
-// MAP CONSTANTS
-pub const MAP_WIDTH: i32 = 58;
-pub const MAP_LENGTH: i32 = 30;
-
 // VIEW CONSTANTS
 pub const VIEWAREA_WIDTH: u32 = 76;
 pub const VIEWAREA_HEIGHT: u32 = 40;
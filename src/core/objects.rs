@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Object {
     pub itemtype: ItemType,
     pub remaining_resources: Vec<(ItemType, u8)>,
@@ -22,7 +23,7 @@ pub struct ItemReplacements {
     pub replacements: Vec<ItemType>,
 }
 
-#[derive(Component, PartialEq, Copy, Clone, Debug, Default)]
+#[derive(Component, PartialEq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum ItemType {
     #[default]
     Log,
@@ -34,6 +35,11 @@ pub enum ItemType {
     Weed,
     Food,
     Berry,
+    CopperOre,
+    IronOre,
+    GoldOre,
+    Bed,
+    Corpse,
 }
 
 impl ItemType {
@@ -45,6 +51,21 @@ impl ItemType {
             ItemType::Tree => MaterialProperties { hardness: 3.0, toughness: 10.0, energy_density: 20.0, mass: 200.0, conductivity: 0.2 },
             ItemType::Bush | ItemType::Berry | ItemType::Food => MaterialProperties { hardness: 0.1, toughness: 0.1, energy_density: 50.0, mass: 0.5, conductivity: 0.9 },
             ItemType::Weed => MaterialProperties { hardness: 0.01, toughness: 0.01, energy_density: 5.0, mass: 0.1, conductivity: 0.95 },
+            // Hard, heavy chunks make good striking tools.
+            ItemType::CopperOre => MaterialProperties { hardness: 4.0, toughness: 4.0, energy_density: 0.0, mass: 4.0, conductivity: 0.9 },
+            ItemType::IronOre => MaterialProperties { hardness: 6.0, toughness: 6.0, energy_density: 0.0, mass: 5.0, conductivity: 0.7 },
+            ItemType::GoldOre => MaterialProperties { hardness: 3.0, toughness: 3.0, energy_density: 0.0, mass: 8.0, conductivity: 0.95 },
+            ItemType::Bed => MaterialProperties { hardness: 1.0, toughness: 2.0, energy_density: 0.0, mass: 20.0, conductivity: 0.3 },
+            // Each corpse carries its own mass and energy; this is only a stand-in.
+            ItemType::Corpse => MaterialProperties { hardness: 0.5, toughness: 0.5, energy_density: 20.0, mass: 1.0, conductivity: 0.5 },
+        }
+    }
+
+    /// What is left when the item is broken, if anything: a felled tree leaves a log.
+    pub fn remains(&self) -> Option<ItemType> {
+        match self {
+            ItemType::Tree => Some(ItemType::Log),
+            _ => None,
         }
     }
 
@@ -59,6 +80,11 @@ impl ItemType {
             ItemType::Berry => 67 * 64 + 57,
             ItemType::Weed => 67 * 64 + 57,
             ItemType::Food => 94 * 64 + 31,
+            ItemType::CopperOre => 51 * 64 + 9,
+            ItemType::IronOre => 51 * 64 + 10,
+            ItemType::GoldOre => 51 * 64 + 11,
+            ItemType::Bed => 19 * 64 + 22,
+            ItemType::Corpse => 94 * 64 + 31,
         }
     }
 
@@ -1,5 +1,8 @@
 ﻿use crate::prelude::*;
 use std::collections::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 
 #[derive(Resource, Default)]
 pub struct TileHash {
@@ -11,6 +14,271 @@ pub struct TileEnvHash {
     pub hash: HashMap<Position, EnvironmentalData>,
 }
 
+/// Standing liquid, as depth per cell (1.0 fills the cell). Water at a position lies on
+/// that position's tile, the same way a unit stands on it.
+#[derive(Resource, Default)]
+pub struct LiquidHash {
+    pub hash: HashMap<Position, f32>,
+    /// Cells that gain this much water every flow step.
+    pub springs: Vec<(Position, f32)>,
+}
+
+impl LiquidHash {
+    pub fn depth(&self, position: &Position) -> f32 {
+        self.hash.get(position).copied().unwrap_or(0.0)
+    }
+}
+
+// One in-game day lasts this many simulation ticks (a minute at the default 64 ticks a second).
+pub const TICKS_PER_DAY: u64 = 64 * 60;
+pub const DAYS_PER_SEASON: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+    /// Temperature open ground drifts towards under the sky.
+    pub fn surface_temperature(&self) -> f32 {
+        match self {
+            Season::Spring => 14.0,
+            Season::Summer => 24.0,
+            Season::Autumn => 12.0,
+            Season::Winter => -2.0,
+        }
+    }
+    /// Humidity of ground with no water nearby.
+    pub fn dry_humidity(&self) -> f32 {
+        match self {
+            Season::Spring => 0.35,
+            Season::Summer => 0.15,
+            Season::Autumn => 0.3,
+            Season::Winter => 0.25,
+        }
+    }
+    /// How fast plants grow, relative to summer.
+    pub fn growth_rate(&self) -> f32 {
+        match self {
+            Season::Spring => 1.5,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.5,
+            Season::Winter => 0.0,
+        }
+    }
+}
+
+/// The simulation clock: ticks run since the colony was founded. Every simulation system
+/// keys its schedule, cooldowns and the in-game date off this one counter.
+#[derive(Resource, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SimClock {
+    pub tick: u64,
+}
+
+impl SimClock {
+    /// Whole days since the start, from 0.
+    pub fn days(&self) -> u64 {
+        self.tick / TICKS_PER_DAY
+    }
+    pub fn season(&self) -> Season {
+        match (self.days() / DAYS_PER_SEASON) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+    /// Day within the current season, from 1.
+    pub fn day_of_season(&self) -> u64 {
+        self.days() % DAYS_PER_SEASON + 1
+    }
+    /// Current year, from 1.
+    pub fn year(&self) -> u64 {
+        self.days() / (DAYS_PER_SEASON * 4) + 1
+    }
+    /// How far through the current day we are, 0.0 at midnight to 1.0.
+    pub fn time_of_day(&self) -> f32 {
+        (self.tick % TICKS_PER_DAY) as f32 / TICKS_PER_DAY as f32
+    }
+    /// Light from the sun, 0.0 through the night to 1.0 from mid-morning to mid-afternoon,
+    /// with dawn at a quarter of the day and dusk at three quarters.
+    pub fn daylight(&self) -> f32 {
+        (((self.time_of_day() - 0.25) * std::f32::consts::TAU).sin() * 1.5 + 0.5).clamp(0.0, 1.0)
+    }
+    pub fn date(&self) -> String {
+        format!("Year {}, {} day {}", self.year(), self.season().name(), self.day_of_season())
+    }
+}
+
+/// An enclosed region of floor on one level, as found by the room detector.
+#[derive(Debug, Clone)]
+pub struct Room {
+    pub id: usize,
+    pub volume: usize,
+    /// Share of the room's edges and ceiling that open onto the outside, 0..1.
+    pub openness: f32,
+    /// How much of the climate change from outside the room is kept out, 0..1.
+    pub insulation: f32,
+}
+
+/// Rooms by id, and the room every enclosed floor tile belongs to.
+/// Tiles that aren't in the hash are outdoors.
+#[derive(Resource, Default)]
+pub struct RoomHash {
+    pub rooms: Vec<Room>,
+    pub hash: HashMap<Position, usize>,
+}
+
+impl RoomHash {
+    pub fn room(&self, position: &Position) -> Option<&Room> {
+        self.hash.get(position).and_then(|id| self.rooms.get(*id))
+    }
+
+    /// Share of a climate change that reaches this tile: 1.0 outdoors, less inside a room.
+    pub fn exposure(&self, position: &Position) -> f32 {
+        1.0 - self.room(position).map_or(0.0, |room| room.insulation)
+    }
+}
+
+// Width in tiles of one spatial index bucket. Queries visit every bucket their area touches.
+const SPATIAL_BUCKET: i32 = 8;
+
+/// Where every positioned entity is, bucketed by area so systems can ask what is near a point
+/// without walking every entity. Map tiles are kept apart, one per position.
+/// Kept in sync with `Position` by `sync_spatial_index` and the `unindex_removed` observer.
+#[derive(Resource, Default)]
+pub struct SpatialIndex {
+    buckets: HashMap<Position, Vec<(Entity, Position)>>,
+    positions: HashMap<Entity, Position>,
+    tiles: HashMap<Position, Entity>,
+    tile_positions: HashMap<Entity, Position>,
+}
+
+impl SpatialIndex {
+    fn bucket(position: &Position) -> Position {
+        Position { x: position.x.div_euclid(SPATIAL_BUCKET), y: position.y.div_euclid(SPATIAL_BUCKET), z: position.z }
+    }
+
+    /// Adds an entity, or moves it if it is already indexed.
+    pub fn insert(&mut self, entity: Entity, position: Position) {
+        if let Some(old) = self.positions.insert(entity, position) {
+            if old == position { return; }
+            self.remove_from_bucket(entity, &old);
+        }
+        self.buckets.entry(Self::bucket(&position)).or_default().push((entity, position));
+    }
+
+    pub fn insert_tile(&mut self, entity: Entity, position: Position) {
+        if let Some(old) = self.tile_positions.insert(entity, position) {
+            self.tiles.remove(&old);
+        }
+        self.tiles.insert(position, entity);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some(old) = self.positions.remove(&entity) {
+            self.remove_from_bucket(entity, &old);
+        }
+        if let Some(old) = self.tile_positions.remove(&entity) {
+            if self.tiles.get(&old) == Some(&entity) {
+                self.tiles.remove(&old);
+            }
+        }
+    }
+
+    fn remove_from_bucket(&mut self, entity: Entity, position: &Position) {
+        let bucket = Self::bucket(position);
+        if let Some(entries) = self.buckets.get_mut(&bucket) {
+            entries.retain(|(e, _)| *e != entity);
+            if entries.is_empty() {
+                self.buckets.remove(&bucket);
+            }
+        }
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<Position> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// The map tile entity at a position.
+    pub fn tile(&self, position: &Position) -> Option<Entity> {
+        self.tiles.get(position).copied()
+    }
+
+    /// Entities standing exactly at a position.
+    pub fn at(&self, position: &Position) -> Vec<Entity> {
+        self.buckets.get(&Self::bucket(position))
+            .map(|entries| entries.iter().filter(|(_, p)| p == position).map(|(e, _)| *e).collect())
+            .unwrap_or_default()
+    }
+
+    /// Entities inside the box from `min` to `max`, both inclusive, sorted by position
+    /// so callers see them in the same order on every run.
+    pub fn in_box(&self, min: &Position, max: &Position) -> Vec<(Entity, Position)> {
+        let (low, high) = (Self::bucket(min), Self::bucket(max));
+        let mut found = Vec::new();
+        for z in min.z..=max.z {
+            for by in low.y..=high.y {
+                for bx in low.x..=high.x {
+                    let Some(entries) = self.buckets.get(&Position { x: bx, y: by, z }) else { continue };
+                    found.extend(entries.iter().filter(|(_, p)| {
+                        (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)
+                    }).copied());
+                }
+            }
+        }
+        found.sort_by_key(|(e, p)| (p.z, p.y, p.x, *e));
+        found
+    }
+
+    /// Entities within `radius` of `center`, by `Position::distance`, sorted like `in_box`.
+    pub fn in_radius(&self, center: &Position, radius: i32) -> Vec<(Entity, Position)> {
+        let min = Position { x: center.x - radius, y: center.y - radius, z: center.z - radius };
+        let max = Position { x: center.x + radius, y: center.y + radius, z: center.z + radius };
+        let mut found = self.in_box(&min, &max);
+        found.retain(|(_, p)| center.distance(p) <= radius);
+        found
+    }
+}
+
+/// Size of the generated world. Tiles span `0..width` x `0..length`, levels `min_z..=max_z`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldConfig {
+    pub width: i32,
+    pub length: i32,
+    pub min_z: i32,
+    pub max_z: i32,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self { width: 58, length: 30, min_z: -10, max_z: 2 }
+    }
+}
+
+impl WorldConfig {
+    pub fn z_levels(&self) -> std::ops::RangeInclusive<i32> {
+        self.min_z..=self.max_z
+    }
+    pub fn clamp_z(&self, z: i32) -> i32 {
+        z.clamp(self.min_z, self.max_z)
+    }
+    pub fn is_border(&self, x: i32, y: i32) -> bool {
+        x == 0 || x == self.width - 1 || y == 0 || y == self.length - 1
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct SpriteSheet {
     pub handle: Handle<Image>,
@@ -74,13 +342,65 @@ pub struct Dragging {
     pub looking_for: SelectableType,
     pub zone_type: ZoneType,
     pub item_type: ItemType,
+    pub tile_type: Option<TileType>,
 }
 
-#[derive(Resource, Default)]
+/// How many simulation ticks run for every tick's worth of real time.
+/// Pausing is done through `GameState::Paused`.
+#[derive(Resource)]
 pub struct GameSpeed {
     pub speed: f32,
 }
 
+impl GameSpeed {
+    // As fast as the machine keeps up with.
+    pub const MAX: f32 = 50.0;
+    /// The speeds the player can pick, with their labels.
+    pub const SETTINGS: [(f32, &'static str); 4] = [(1.0, "1x"), (2.0, "2x"), (5.0, "5x"), (GameSpeed::MAX, "Max")];
+
+    pub fn label(&self) -> &'static str {
+        GameSpeed::SETTINGS.iter()
+            .find(|(speed, _)| *speed == self.speed)
+            .map_or("?", |(_, label)| label)
+    }
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self { speed: 1.0 }
+    }
+}
+
+/// The simulation's only source of randomness. Every system draws from its own stream,
+/// derived from the world seed and the stream's name, so the same seed gives the same run
+/// no matter in which order systems happen to be scheduled.
+#[derive(Resource)]
+pub struct SimRng {
+    pub seed: u64,
+    streams: HashMap<&'static str, StdRng>,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, streams: HashMap::new() }
+    }
+    pub fn stream(&mut self, name: &'static str) -> &mut StdRng {
+        let seed = self.seed;
+        self.streams.entry(name).or_insert_with(|| {
+            // FNV-1a over the name, so streams don't depend on std's hasher.
+            let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+            StdRng::seed_from_u64(seed ^ hash)
+        })
+    }
+}
+
+impl Default for SimRng {
+    // Without an explicit seed every run gets a fresh one. It's kept small so it's easy to type back in.
+    fn default() -> Self {
+        Self::new(rand::random::<u32>() as u64)
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct CurrentDisplayZ {
     pub z: i32,
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use bevy::app::AppExit;
+use bevy::log::LogPlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use crate::simulation::clock::TICKS_PER_SECOND;
+use std::time::Duration;
+
+pub const DEFAULT_HEADLESS_TICKS: u32 = 10_000;
+
+/// How many simulation ticks are left before a headless run exits.
+#[derive(Resource)]
+pub struct TicksRemaining(pub u32);
+
+// Skips the main menu and stops the app after the requested number of ticks.
+pub struct HeadlessPlugin {
+    pub ticks: u32,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .insert_resource(TicksRemaining(self.ticks))
+        // Advance time by exactly one fixed step per frame, so every frame is one tick
+        // no matter how fast the machine is.
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / TICKS_PER_SECOND)))
+        .add_systems(Startup, start_game)
+        .add_systems(FixedPostUpdate, count_ticks.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn start_game(
+    mut next_state: ResMut<NextState<GameState>>,
+    sim_rng: Res<SimRng>,
+) {
+    info!("Headless run with seed {}", sim_rng.seed);
+    next_state.set(GameState::Initializing);
+}
+
+fn count_ticks(
+    mut ticks: ResMut<TicksRemaining>,
+    mut exit: MessageWriter<AppExit>,
+    units: Query<&ActorType>,
+    objects: Query<&Object>,
+    sim_rng: Res<SimRng>,
+) {
+    ticks.0 = ticks.0.saturating_sub(1);
+    if ticks.0 > 0 { return; }
+    info!("Headless run with seed {} finished with {} units and {} objects", sim_rng.seed, units.iter().count(), objects.iter().count());
+    exit.write(AppExit::Success);
+}
+
+/// Runs the simulation for `ticks` fixed ticks without a window, renderer or audio.
+pub fn run_headless(ticks: u32, sim_rng: SimRng, world: WorldConfig) -> AppExit {
+    App::new()
+        .insert_resource(sim_rng)
+        .insert_resource(world)
+        .add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            LogPlugin::default(),
+        ))
+        .add_plugins((
+            crate::simulation::SimulationPlugin,
+            HeadlessPlugin { ticks },
+        ))
+        .run()
+}
@@ -1,10 +1,13 @@
 ﻿use crate::prelude::*;
+use crate::initializations::noise::Noise2D;
+use crate::simulation::liquids::SPRING_RATE;
+use std::collections::HashSet;
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, _app: &mut App) {
-        // Map is generated once at Startup in main.rs, 
+        // Map is generated once at Startup by SimulationPlugin, 
         // no need to re-generate on state change.
     }
 }
@@ -12,33 +15,61 @@ impl Plugin for MapPlugin {
 pub fn generate_map(
     mut commands: Commands,
     biome: Res<Biome>,
+    world: Res<WorldConfig>,
     mut tiletypes: ResMut<TileHash>,
     mut tileenvs: ResMut<TileEnvHash>,
-    sprite_sheet: Res<SpriteSheet>,
+    mut liquids: ResMut<LiquidHash>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    for x in 0..MAP_WIDTH {
-        for y in 0..MAP_LENGTH {
-            // Simple hill generation
-            let hill_height = ((x as f32 * 0.1).sin() * (y as f32 * 0.1).cos() * 2.0).round() as i32;
-            
-            for z in -3..3 {
-                let is_border = x == 0 || x == MAP_WIDTH - 1 || y == 0 || y == MAP_LENGTH - 1;
-                
-                let tyle_type = if is_border {
+    let rng = sim_rng.stream("map");
+    let terrain = Terrain::new(rng);
+    let height_at = |x: i32, y: i32| terrain.height(&world, x, y);
+    let water = terrain.surface_water(&world, rng);
+
+    for x in 0..world.width {
+        for y in 0..world.length {
+            let hill_height = height_at(x, y);
+            let soil_depth = terrain.soil_depth(x, y);
+            // Natural height steps get a ramp so units can walk up onto the next level.
+            let at_step = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+                .any(|(dx, dy)| !world.is_border(x + dx, y + dy) && height_at(x + dx, y + dy) == hill_height + 1);
+
+            let bed = water.beds.get(&(x, y)).copied();
+            // Lakes and ponds sit in a basin one level down, so they hold their water.
+            let basin = bed.is_some_and(is_basin);
+
+            for z in world.z_levels() {
+                let tyle_type = if world.is_border(x, y) {
                     if z <= hill_height { TileType::WallGame } else { TileType::Void }
                 } else {
-                    if z < hill_height {
-                        TileType::Dirt
+                    if z > hill_height || (z == hill_height && basin) {
+                        TileType::Void
+                    } else if z == hill_height - 1 && basin {
+                        TileType::Gravel
+                    } else if z == hill_height && at_step {
+                        TileType::Ramp
+                    } else if z == hill_height && bed.is_some() {
+                        TileType::Gravel
                     } else if z == hill_height {
-                        biome.tiles.choose(&mut rand::rng()).unwrap_or(&TileType::Grass).clone()
-                    } else {
+                        *biome.tiles.choose(rng).unwrap_or(&TileType::Grass)
+                    } else if z >= hill_height - soil_depth {
+                        TileType::Dirt
+                    } else if terrain.is_cave(x, y, z - 1, hill_height - soil_depth) {
+                        // Open air above a cave floor.
                         TileType::Void
+                    } else if terrain.is_cave(x, y, z, hill_height - soil_depth) {
+                        TileType::CaveFloor
+                    } else {
+                        // Depth into the bedrock, counted from the bottom of the soil.
+                        let depth = hill_height - soil_depth - z;
+                        terrain.pocket(&world, x, y, z, depth)
+                            .or_else(|| terrain.ore(x, y, z, depth))
+                            .unwrap_or_else(|| terrain.rock(x, y, z, depth))
                     }
                 };
 
                 if tyle_type == TileType::Void { continue; }
                 
-                let (tx, ty) = tyle_type.get_texture_coords();
                 let position = Position { x, y, z };
 
                 let tx_f = x as f32;
@@ -54,29 +85,191 @@ pub fn generate_map(
                     } * (0.5 + (tx_f * 0.1).sin() * 0.5),
                 };
                 
-                commands.spawn((
-                    MapTile,
-                    position,
-                    tyle_type.clone(),
-                    env_data.clone(),
-                    tyle_type.material_properties(),
-                    SizeXYZ::flat(TILE_SIZE),
-                    Sprite {
-                        image: sprite_sheet.handle.clone(),
-                        texture_atlas: Some(TextureAtlas {
-                            layout: sprite_sheet.layout.clone(),
-                            index: (tx + ty * 64) as usize,
-                        }),
-                        ..default()
-                    },
-                    position.to_transform(),
-                    Visibility::default(),
-                ));
+                spawn_map_tile(&mut commands, position, tyle_type, env_data.clone());
 
                 tiletypes.hash.insert(position, tyle_type);
                 tileenvs.hash.insert(position, env_data);
             }
         }
     }
+
+    liquids.hash.clear();
+    liquids.springs.clear();
+    for ((x, y), depth) in water.beds.iter() {
+        let z = height_at(*x, *y) - is_basin(*depth) as i32;
+        liquids.hash.insert(Position { x: *x, y: *y, z }, *depth);
+    }
+    for (x, y) in water.springs {
+        liquids.springs.push((Position { x, y, z: height_at(x, y) }, SPRING_RATE));
+    }
 }
 
+fn is_basin(depth: f32) -> bool {
+    depth >= 1.0
+}
+
+/// Spawns the entity for one map tile. Its sprite is attached by the rendering side, if any.
+pub fn spawn_map_tile(
+    commands: &mut Commands,
+    position: Position,
+    tile_type: TileType,
+    env_data: EnvironmentalData,
+) -> Entity {
+    commands.spawn((
+        MapTile,
+        position,
+        tile_type,
+        env_data,
+        tile_type.material_properties(),
+        SizeXYZ::flat(TILE_SIZE),
+        position.to_transform(),
+        Visibility::default(),
+    )).id()
+}
+
+/// The noise layers the map is carved from.
+pub struct Terrain {
+    height: Noise2D,
+    soil: Noise2D,
+    strata: Noise2D,
+    caves: Noise2D,
+    ores: Noise2D,
+    pockets: Noise2D,
+    lakes: Noise2D,
+}
+
+/// Water laid on the surface at generation: lake and river beds with their starting depth,
+/// and the springs that keep the rivers running.
+pub struct SurfaceWater {
+    pub beds: HashMap<(i32, i32), f32>,
+    pub springs: Vec<(i32, i32)>,
+}
+
+// Surface hills span at most this many levels; everything further down is underground.
+const MAX_RELIEF: i32 = 4;
+
+impl Terrain {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        Self {
+            height: Noise2D::new(rng.random()),
+            soil: Noise2D::new(rng.random()),
+            strata: Noise2D::new(rng.random()),
+            caves: Noise2D::new(rng.random()),
+            ores: Noise2D::new(rng.random()),
+            pockets: Noise2D::new(rng.random()),
+            lakes: Noise2D::new(rng.random()),
+        }
+    }
+
+    /// Surface level of a column: rolling hills in the top levels of the world,
+    /// always leaving at least one level of rock underneath.
+    pub fn height(&self, world: &WorldConfig, x: i32, y: i32) -> i32 {
+        let n = self.height.fbm(x as f32 * 0.04, y as f32 * 0.04, 4);
+        // fbm bunches up around 0.5; stretch it so the whole range gets used.
+        let n = ((n - 0.5) * 1.8 + 0.5).clamp(0.0, 1.0);
+        let lowest = (world.max_z - MAX_RELIEF).max(world.min_z + 1).min(world.max_z);
+        lowest + (n * (world.max_z - lowest) as f32).round() as i32
+    }
+
+    /// How many levels of dirt sit under the surface tile (0..=2).
+    pub fn soil_depth(&self, x: i32, y: i32) -> i32 {
+        (self.soil.fbm(x as f32 * 0.1, y as f32 * 0.1, 2) * 2.99) as i32
+    }
+
+    /// Whether level `z` of a column is open cave, with its floor at `z`. Caves are winding
+    /// tunnels (a thin band around the noise's midpoint) opening into chambers, and always
+    /// keep at least one level of rock between them and the soil above.
+    pub fn is_cave(&self, x: i32, y: i32, z: i32, rock_top: i32) -> bool {
+        if z + 2 > rock_top { return false; }
+        let n = self.caves.fbm(x as f32 * 0.08, y as f32 * 0.08 + z as f32 * 41.3, 3);
+        (n - 0.5).abs() < 0.035 || n > 0.77
+    }
+
+    /// Rock below the soil: stone, with layers of gravel bending through it,
+    /// giving way to granite further down.
+    pub fn rock(&self, x: i32, y: i32, z: i32, depth: i32) -> TileType {
+        let band = self.strata.fbm(x as f32 * 0.06, y as f32 * 0.06 + z as f32 * 11.7, 2);
+        if band > 0.65 {
+            TileType::Gravel
+        } else if depth + (band * 4.0) as i32 > 6 {
+            TileType::Granite
+        } else {
+            TileType::Stone
+        }
+    }
+
+    /// Ore veins. Copper lies closest to the surface, iron deeper, gold only in the depths.
+    pub fn ore(&self, x: i32, y: i32, z: i32, depth: i32) -> Option<TileType> {
+        let vein = self.ores.fbm(x as f32 * 0.15, y as f32 * 0.15 + z as f32 * 23.9, 3);
+        if vein < 0.7 { return None; }
+        Some(match depth {
+            0..=2 => TileType::CopperOre,
+            3..=5 => if vein > 0.76 { TileType::IronOre } else { TileType::CopperOre },
+            _ => if vein > 0.78 { TileType::GoldOre } else { TileType::IronOre },
+        })
+    }
+
+    /// Sealed pockets inside the rock: water near the top, magma at the bottom of the world,
+    /// loose gravel in between.
+    pub fn pocket(&self, world: &WorldConfig, x: i32, y: i32, z: i32, depth: i32) -> Option<TileType> {
+        let n = self.pockets.fbm(x as f32 * 0.2, y as f32 * 0.2 + z as f32 * 7.1, 2);
+        if n < 0.76 { return None; }
+        Some(if z <= world.min_z + 1 {
+            TileType::Magma
+        } else if depth <= 2 {
+            TileType::Water
+        } else {
+            TileType::Gravel
+        })
+    }
+
+    /// Lakes fill patches of the lowest ground. Springs rise on high ground, and each one's
+    /// river runs downhill from it until it reaches a lake, a hollow or the edge of the map.
+    pub fn surface_water(&self, world: &WorldConfig, rng: &mut impl Rng) -> SurfaceWater {
+        let mut beds = HashMap::new();
+        let mut springs = Vec::new();
+        let interior: Vec<(i32, i32)> = (1..world.width - 1)
+            .flat_map(|x| (1..world.length - 1).map(move |y| (x, y)))
+            .collect();
+        let Some(lowest) = interior.iter().map(|(x, y)| self.height(world, *x, *y)).min() else {
+            return SurfaceWater { beds, springs };
+        };
+
+        for (x, y) in interior.iter() {
+            if self.height(world, *x, *y) == lowest && self.lakes.fbm(*x as f32 * 0.08, *y as f32 * 0.08, 2) > 0.55 {
+                beds.insert((*x, *y), 1.0);
+            }
+        }
+
+        let count = 1 + world.width * world.length / 1000;
+        for _ in 0..count {
+            // The highest of a handful of random spots.
+            let Some(source) = (0..20)
+                .map(|_| interior[rng.random_range(0..interior.len())])
+                .max_by_key(|(x, y)| self.height(world, *x, *y)) else { continue };
+            springs.push(source);
+
+            let mut current = source;
+            let mut visited = HashSet::new();
+            loop {
+                visited.insert(current);
+                if beds.get(&current).is_some_and(|depth| *depth >= 1.0) { break; }
+                beds.insert(current, 0.5);
+                let height = self.height(world, current.0, current.1);
+                let next = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+                    .map(|(dx, dy)| (current.0 + dx, current.1 + dy))
+                    .filter(|p| !world.is_border(p.0, p.1) && !visited.contains(p))
+                    .min_by_key(|p| self.height(world, p.0, p.1));
+                match next {
+                    Some(p) if self.height(world, p.0, p.1) <= height => current = p,
+                    // Nowhere lower to go: the river pools into a pond.
+                    _ => {
+                        beds.insert(current, 1.0);
+                        break;
+                    }
+                }
+            }
+        }
+        SurfaceWater { beds, springs }
+    }
+}
@@ -4,6 +4,9 @@ pub mod load;
 pub use load::*;
 pub mod map;
 pub use map::*;
+pub mod noise;
+pub mod save;
+pub use save::*;
 pub mod startup;
 pub use startup::*;
 pub mod window_system;
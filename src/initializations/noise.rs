@@ -0,0 +1,51 @@
+// Seeded value noise for terrain generation. Each layer (height, soil, strata) gets
+// its own `Noise2D`, seeded from the map's random stream.
+pub struct Noise2D {
+    seed: u64,
+}
+
+impl Noise2D {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// A value in 0.0..1.0 fixed for every integer lattice point.
+    fn lattice(&self, x: i32, y: i32) -> f32 {
+        // splitmix64 finaliser over the seed and coordinates.
+        let mut h = self.seed
+            ^ (x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^= h >> 31;
+        (h >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Smoothly interpolated noise in 0.0..1.0.
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let fade = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (fade(x - x0 as f32), fade(y - y0 as f32));
+        let top = self.lattice(x0, y0) * (1.0 - tx) + self.lattice(x0 + 1, y0) * tx;
+        let bottom = self.lattice(x0, y0 + 1) * (1.0 - tx) + self.lattice(x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Several octaves of `sample` added together, each at double the frequency and half the weight
+    /// of the last. Still in 0.0..1.0.
+    pub fn fbm(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let mut total = 0.0;
+        let mut weight = 1.0;
+        let mut frequency = 1.0;
+        let mut max = 0.0;
+        for octave in 0..octaves {
+            // Offset every octave so their lattices don't line up.
+            let offset = octave as f32 * 17.3;
+            total += self.sample(x * frequency + offset, y * frequency - offset) * weight;
+            max += weight;
+            weight *= 0.5;
+            frequency *= 2.0;
+        }
+        total / max
+    }
+}
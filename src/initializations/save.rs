@@ -0,0 +1,306 @@
+use crate::prelude::*;
+use crate::initializations::{finalize_initialization, spawn_map_tile};
+use crate::simulation::unitgenerator_system::UnitTemplate;
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::time::common_conditions::on_timer;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Bump whenever the layout of `SaveFile` changes in a way old files can't be read into.
+pub const SAVE_FORMAT_VERSION: u32 = 8;
+pub const SAVE_PATH: &str = "saves/colony.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_systems(
+            Update,
+            save_game_system
+                .run_if(input_just_pressed(KeyCode::F5).or(on_timer(Duration::from_secs(300))))
+                .run_if(in_state(GameState::InGame).or(in_state(GameState::Paused)))
+        )
+        .add_systems(
+            OnEnter(GameState::Initializing),
+            load_game_system
+                .run_if(resource_exists::<LoadGameRequest>)
+                .before(finalize_initialization)
+        )
+        ;
+    }
+}
+
+/// Set by the main menu's "Load Game" button. While present, `StartupPlugin` skips
+/// spawning a fresh colony and `load_game_system` rebuilds the saved one instead.
+#[derive(Resource)]
+pub struct LoadGameRequest(pub SaveFile);
+
+/// Everything needed to rebuild a running game, except the state of the random streams:
+/// those can't be written out, so a loaded game restarts them from `seed` and does not
+/// replay the run it was saved from.
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub seed: u64,
+    pub world: WorldConfig,
+    pub clock: SimClock,
+    pub tiles: Vec<(Position, TileType)>,
+    pub environment: Vec<(Position, EnvironmentalData)>,
+    pub liquids: Vec<(Position, f32)>,
+    pub springs: Vec<(Position, f32)>,
+    pub units: Vec<SavedUnit>,
+    pub objects: Vec<SavedObject>,
+    pub generators: Vec<SavedGenerator>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedUnit {
+    pub id: Entity,
+    pub position: Position,
+    pub actor_type: ActorType,
+    pub material: MaterialProperties,
+    pub body: PhysicalBody,
+    pub brain: Brain,
+    pub genome: Option<Genome>,
+    pub generation: Option<Generation>,
+    pub reproduction: Option<ReproductionStatus>,
+    pub name: Option<HasName>,
+    pub nest: Option<Nest>,
+    pub asleep: Option<Asleep>,
+    /// The monster generator that spawned it, which waits until it is gone.
+    pub generated_by: Option<Entity>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedObject {
+    pub id: Entity,
+    pub position: Position,
+    pub object: Object,
+    pub material: MaterialProperties,
+    pub plant: Option<Plant>,
+    pub food: Option<Food>,
+    pub corpse: Option<Corpse>,
+    pub held_by: Option<Entity>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedGenerator {
+    pub id: Entity,
+    pub position: Position,
+    pub monsters: Vec<(ActorType, u32)>,
+}
+
+pub fn write_save(save: &SaveFile, path: &str) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+pub fn read_save(path: &str) -> Result<SaveFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let save: SaveFile = ron::from_str(&text).map_err(|e| e.to_string())?;
+    if save.version != SAVE_FORMAT_VERSION {
+        return Err(format!("save format version {} is not supported (expected {})", save.version, SAVE_FORMAT_VERSION));
+    }
+    Ok(save)
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn save_game_system(
+    units: Query<(Entity, &Position, &ActorType, &MaterialProperties, &PhysicalBody, &Brain, Option<&Genome>, Option<&Generation>, Option<&ReproductionStatus>, Option<&HasName>, Option<&Nest>, Option<&Asleep>, Option<&GeneratedBy>)>,
+    objects: Query<(Entity, &Position, &Object, &MaterialProperties, Option<&Plant>, Option<&Food>, Option<&Corpse>, Option<&ChildOf>)>,
+    generators: Query<(Entity, &Position, &MonsterGenerator)>,
+    tiletypes: Res<TileHash>,
+    tileenvs: Res<TileEnvHash>,
+    liquids: Res<LiquidHash>,
+    sim_rng: Res<SimRng>,
+    world: Res<WorldConfig>,
+    clock: Res<SimClock>,
+) {
+    let save = SaveFile {
+        version: SAVE_FORMAT_VERSION,
+        seed: sim_rng.seed,
+        world: *world,
+        clock: *clock,
+        tiles: tiletypes.hash.iter().map(|(p, t)| (*p, *t)).collect(),
+        environment: tileenvs.hash.iter().map(|(p, e)| (*p, e.clone())).collect(),
+        liquids: liquids.hash.iter().map(|(p, d)| (*p, *d)).collect(),
+        springs: liquids.springs.clone(),
+        units: units.iter().map(|(id, position, actor_type, material, body, brain, genome, generation, reproduction, name, nest, asleep, generated_by)| SavedUnit {
+            id,
+            position: *position,
+            actor_type: *actor_type,
+            material: material.clone(),
+            body: body.clone(),
+            brain: brain.clone(),
+            genome: genome.cloned(),
+            generation: generation.cloned(),
+            reproduction: reproduction.cloned(),
+            name: name.cloned(),
+            nest: nest.cloned(),
+            asleep: asleep.cloned(),
+            generated_by: generated_by.map(|g| g.entity),
+        }).collect(),
+        objects: objects.iter().map(|(id, position, object, material, plant, food, corpse, parent)| SavedObject {
+            id,
+            position: *position,
+            object: object.clone(),
+            material: material.clone(),
+            plant: plant.cloned(),
+            food: food.cloned(),
+            corpse: corpse.copied(),
+            held_by: parent.map(|p| p.0),
+        }).collect(),
+        generators: generators.iter().map(|(id, position, generator)| SavedGenerator {
+            id,
+            position: *position,
+            monsters: generator.monsters.iter().map(|(template, weight)| (template.actor_type, *weight)).collect(),
+        }).collect(),
+    };
+    match write_save(&save, SAVE_PATH) {
+        Ok(()) => info!("Saved {} units and {} objects to {}", save.units.len(), save.objects.len(), SAVE_PATH),
+        Err(e) => warn!("Could not save game to {}: {}", SAVE_PATH, e),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn load_game_system(
+    mut commands: Commands,
+    request: Res<LoadGameRequest>,
+    map_tiles: Query<Entity, With<MapTile>>,
+    mut tiletypes: ResMut<TileHash>,
+    mut tileenvs: ResMut<TileEnvHash>,
+    mut liquids: ResMut<LiquidHash>,
+    mut sim_rng: ResMut<SimRng>,
+    mut world: ResMut<WorldConfig>,
+    mut clock: ResMut<SimClock>,
+) {
+    let save = &request.0;
+    *world = save.world;
+    *clock = save.clock;
+    // Random streams restart from the saved world seed rather than where they were, so the
+    // game goes on differently from how the saved run would have.
+    *sim_rng = SimRng::new(save.seed);
+    let rng = sim_rng.stream("load");
+
+    // The map generated at startup is replaced wholesale.
+    for entity in map_tiles.iter() {
+        commands.entity(entity).despawn();
+    }
+    tiletypes.hash.clear();
+    tileenvs.hash.clear();
+    tileenvs.hash.extend(save.environment.iter().cloned());
+    for (position, tile_type) in save.tiles.iter() {
+        let env_data = tileenvs.hash.get(position).cloned().unwrap_or_default();
+        spawn_map_tile(&mut commands, *position, *tile_type, env_data);
+        tiletypes.hash.insert(*position, *tile_type);
+    }
+    liquids.hash = save.liquids.iter().copied().collect();
+    liquids.springs = save.springs.clone();
+
+    // Reserve every entity first so references between them (targets, held tools, the
+    // generator a monster came from) can be remapped.
+    let mut ids: HashMap<Entity, Entity> = HashMap::new();
+    for unit in save.units.iter() {
+        ids.insert(unit.id, commands.spawn_empty().id());
+    }
+    for object in save.objects.iter() {
+        ids.insert(object.id, commands.spawn_empty().id());
+    }
+    for generator in save.generators.iter() {
+        ids.insert(generator.id, commands.spawn_empty().id());
+    }
+
+    for unit in save.units.iter() {
+        let entity = ids[&unit.id];
+        let mut body = unit.body.clone();
+        if let Some(danger) = body.danger.as_mut() {
+            danger.danger_source = danger.danger_source.and_then(|e| ids.get(&e).copied());
+        }
+        let scale = unit.genome.as_ref().map_or(1.0, |g| g.size);
+        commands.entity(entity).insert((
+            unit.position.to_transform().with_scale(Vec3::splat(scale)),
+            Visibility::default(),
+            unit.position,
+            unit.actor_type,
+            unit.material.clone(),
+            body,
+            remap_brain(&unit.brain, &ids),
+        ));
+        // Whatever the kind's template adds on spawn; what was saved then takes its place.
+        for builder in &UnitTemplate::from_actor_type(unit.actor_type, rng).component_builders {
+            builder(&mut commands, entity);
+        }
+        if let Some(genome) = &unit.genome { commands.entity(entity).insert(genome.clone()); }
+        if let Some(generation) = &unit.generation { commands.entity(entity).insert(generation.clone()); }
+        if let Some(reproduction) = &unit.reproduction { commands.entity(entity).insert(reproduction.clone()); }
+        if let Some(name) = &unit.name { commands.entity(entity).insert(name.clone()).remove::<GiveMeAName>(); }
+        if let Some(nest) = &unit.nest { commands.entity(entity).insert(nest.clone()).remove::<SetNest>(); }
+        if let Some(asleep) = &unit.asleep { commands.entity(entity).insert(asleep.clone()); }
+        if let Some(generator) = unit.generated_by.and_then(|g| ids.get(&g)) { commands.entity(entity).insert(GeneratedBy { entity: *generator }); }
+    }
+
+    for object in save.objects.iter() {
+        let entity = ids[&object.id];
+        commands.entity(entity).insert((
+            object.position.to_transform(),
+            object.position,
+            object.object.clone(),
+            object.material.clone(),
+            Visibility::default(),
+        ));
+        if let Some(plant) = &object.plant { commands.entity(entity).insert(plant.clone()); }
+        if let Some(food) = &object.food { commands.entity(entity).insert(food.clone()); }
+        if let Some(corpse) = object.corpse { commands.entity(entity).insert(corpse); }
+        if let Some(parent) = object.held_by.and_then(|p| ids.get(&p)) {
+            commands.entity(*parent).add_child(entity);
+            commands.entity(entity).insert(Visibility::Hidden);
+        }
+    }
+
+    for generator in save.generators.iter() {
+        commands.entity(ids[&generator.id]).insert((
+            generator.position.to_transform(),
+            generator.position,
+            SizeXYZ::cube(1.0),
+            MonsterGenerator {
+                monsters: generator.monsters.iter().map(|(actor_type, weight)| (UnitTemplate::from_actor_type(*actor_type, rng), *weight)).collect(),
+            },
+            Visibility::default(),
+        ));
+    }
+
+    info!("Loaded {} units and {} objects", save.units.len(), save.objects.len());
+    commands.remove_resource::<LoadGameRequest>();
+}
+
+/// Points a saved brain's plans and memories at the freshly spawned entities,
+/// dropping anything that referred to an entity that wasn't saved.
+fn remap_brain(brain: &Brain, ids: &HashMap<Entity, Entity>) -> Brain {
+    let remap_action = |action: &AtomicAction| -> Option<AtomicAction> {
+        Some(match *action {
+            AtomicAction::Move(p) => AtomicAction::Move(p),
+            AtomicAction::ApplyForce(e, force) => AtomicAction::ApplyForce(*ids.get(&e)?, force),
+            AtomicAction::Consume(e) => AtomicAction::Consume(*ids.get(&e)?),
+            AtomicAction::Link(a, b) => AtomicAction::Link(*ids.get(&a)?, *ids.get(&b)?),
+            AtomicAction::Scan => AtomicAction::Scan,
+            AtomicAction::Drink(p) => AtomicAction::Drink(p),
+            AtomicAction::Rest => AtomicAction::Rest,
+            AtomicAction::Attack(e) => AtomicAction::Attack(*ids.get(&e)?),
+            AtomicAction::Throw(e) => AtomicAction::Throw(*ids.get(&e)?),
+        })
+    };
+    let mut brain = brain.clone();
+    brain.action = brain.action.as_ref().and_then(remap_action);
+    brain.action_queue = brain.action_queue.iter().filter_map(remap_action).collect();
+    brain.memory = brain.memory.iter().filter_map(|memory| match memory {
+        Memory::Seen { entity, position, tick, material, item } => ids.get(entity).map(|e| Memory::Seen {
+            entity: *e, position: *position, tick: *tick, material: material.clone(), item: *item,
+        }),
+        Memory::Learned { .. } | Memory::Water { .. } => Some(memory.clone()),
+    }).collect();
+    brain
+}
@@ -1,5 +1,6 @@
 ﻿use bevy::prelude::*;
 use crate::prelude::*;
+use crate::initializations::LoadGameRequest;
 use crate::simulation::unitgenerator_system::{spawn_unit_from_template, UnitTemplate};
 use std::collections::HashMap;
 
@@ -7,7 +8,10 @@ pub struct StartupPlugin;
 
 impl Plugin for StartupPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Initializing), (spawn_settlers, spawn_starting_stuff, finalize_initialization).chain());
+        app.add_systems(OnEnter(GameState::Initializing), (
+            (spawn_settlers, spawn_starting_stuff).chain().run_if(not(resource_exists::<LoadGameRequest>)),
+            finalize_initialization,
+        ).chain());
     }
 }
 
@@ -19,82 +23,51 @@ pub fn finalize_initialization(
 
 pub fn spawn_settlers(
     mut commands: Commands,
-    sprite_sheet: Res<SpriteSheet>,
-    mesh_assets: Res<UniversalMeshAssets>,
     tilehash: Res<TileHash>,
+    world: Res<WorldConfig>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    for i in 0..MAP_WIDTH {
+    let rng = sim_rng.stream("settlers");
+    for i in 0..world.width {
         let x = i;
-        let y = MAP_LENGTH / 2;
-
-        let mut spawn_z = 0;
-        for z in (-3..3).rev() {
-            if let Some(tile) = tilehash.hash.get(&Position { x: x as i32, y: y as i32, z }) {
-                if !tile.is_wall() {
-                    spawn_z = z;
-                    break;
-                }
-            }
-        }
+        let y = world.length / 2;
+
+        let spawn_z = surface_z(&tilehash, &world, x, y);
 
-        let position = Position { x: x as i32, y: y as i32, z: spawn_z };
-        if i == MAP_WIDTH / 2 {
-            spawn_unit_from_template(&mut commands, position, &sprite_sheet, &UnitTemplate::elf(), &mesh_assets);
+        let position = Position { x, y, z: spawn_z };
+        if i == world.width / 2 {
+            spawn_unit_from_template(&mut commands, position, &UnitTemplate::elf(rng), rng);
         }
-        if i == MAP_WIDTH / 2 + 1 {
-            spawn_unit_from_template(&mut commands, position, &sprite_sheet, &UnitTemplate::dwarf(), &mesh_assets);
+        if i == world.width / 2 + 1 {
+            spawn_unit_from_template(&mut commands, position, &UnitTemplate::dwarf(rng), rng);
         }
-        if i == MAP_WIDTH / 2 + 2 {
-            spawn_unit_from_template(&mut commands, position, &sprite_sheet, &UnitTemplate::human(), &mesh_assets);
+        if i == world.width / 2 + 2 {
+            spawn_unit_from_template(&mut commands, position, &UnitTemplate::human(rng), rng);
         }
     }
 
-    let x = MAP_WIDTH / 2;
-    let y = MAP_LENGTH / 2 - 2;
-    let mut spawn_z = 0;
-    for z in (-3..3).rev() {
-        if let Some(tile) = tilehash.hash.get(&Position { x: x as i32, y: y as i32, z }) {
-            if !tile.is_wall() {
-                spawn_z = z;
-                break;
-            }
-        }
-    }
-    let position = Position { x: x as i32, y: y as i32, z: spawn_z };
-    spawn_unit_from_template(&mut commands, position, &sprite_sheet, &UnitTemplate::crab(), &mesh_assets);
+    let x = world.width / 2;
+    let y = world.length / 2 - 2;
+    let spawn_z = surface_z(&tilehash, &world, x, y);
+    let position = Position { x, y, z: spawn_z };
+    spawn_unit_from_template(&mut commands, position, &UnitTemplate::crab(), rng);
 }
 
 pub fn spawn_starting_stuff(
     mut commands: Commands,
     biome: Res<Biome>,
-    sprite_sheet: Res<SpriteSheet>,
     tilehash: Res<TileHash>,
+    liquids: Res<LiquidHash>,
+    world: Res<WorldConfig>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    let x = MAP_WIDTH / 2;
-    let y = MAP_LENGTH / 2 + 3;
-    let mut spawn_z = 0;
-    for z in (-3..3).rev() {
-        if let Some(tile) = tilehash.hash.get(&Position { x: x as i32, y: y as i32, z }) {
-            if !tile.is_wall() {
-                spawn_z = z;
-                break;
-            }
-        }
-    }
-    let position = Position { x: x as i32, y: y as i32, z: spawn_z };
+    let x = world.width / 2;
+    let y = world.length / 2 + 3;
+    let spawn_z = surface_z(&tilehash, &world, x, y);
+    let position = Position { x, y, z: spawn_z };
     
     commands
-        .spawn((
-            Sprite {
-                image: sprite_sheet.handle.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: sprite_sheet.layout.clone(),
-                    index: ItemType::Statue.sprite_index(),
-                }),
-                ..default()
-            },
-            position.to_transform(),
-        ))
+        .spawn(position.to_transform())
         .insert(position)
         .insert(SizeXYZ::cube(1.0))
         .insert(MonsterGenerator { monsters: vec![(UnitTemplate::rat(),1),(UnitTemplate::spider(),5),(UnitTemplate::cyclops(),1)] })
@@ -102,42 +75,24 @@ pub fn spawn_starting_stuff(
 
     // GENERATE PLANTS
     let mut taken_positions: HashMap<Position, u8> = HashMap::new();
-    let mut rng = rand::rng();
+    let rng = sim_rng.stream("starting_stuff");
 
-    for _ in 0..(MAP_WIDTH*MAP_LENGTH / 10) {
-        let x = rng.random_range(1..MAP_WIDTH-1);
-        let y = rng.random_range(1..MAP_LENGTH-1);
+    for _ in 0..(world.width*world.length / 10) {
+        let x = rng.random_range(1..world.width-1);
+        let y = rng.random_range(1..world.length-1);
         
-        let mut spawn_z = 0;
-        for z in (-3..3).rev() {
-            if let Some(tile) = tilehash.hash.get(&Position { x: x as i32, y: y as i32, z }) {
-                if !tile.is_wall() {
-                    spawn_z = z;
-                    break;
-                }
-            }
-        }
+        let spawn_z = surface_z(&tilehash, &world, x, y);
 
         let growth = rng.random_range(0.1..1.0);
-        let position = Position { x: x as i32, y: y as i32, z: spawn_z };
-        if taken_positions.contains_key(&position) { continue; }
+        let position = Position { x, y, z: spawn_z };
+        if taken_positions.contains_key(&position) || liquids.depth(&position) > 0.0 { continue; }
         taken_positions.insert(position, 1);
         
         if biome.plants.is_empty() { continue; }
         let plant_type = biome.plants[rng.random_range(0..biome.plants.len())];
 
         let _plant = commands
-            .spawn((
-                Sprite {
-                    image: sprite_sheet.handle.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: sprite_sheet.layout.clone(),
-                        index: plant_type.sprite_index(),
-                    }),
-                    ..default()
-                },
-                position.to_transform(),
-            ))
+            .spawn(position.to_transform())
             .insert(position)
             .insert(Plant { growth, plant_type })
             .insert(Object { itemtype: plant_type, ..default() })
@@ -148,44 +103,55 @@ pub fn spawn_starting_stuff(
     
     // Spawn Objects (Items)
     let scarcity = biome.objects_overall_scarcity.max(1) as i32;
-    for _ in 0..(MAP_WIDTH*MAP_LENGTH / scarcity) {
-        let x = rng.random_range(1..MAP_WIDTH-1) as i32;
-        let y = rng.random_range(1..MAP_LENGTH-1) as i32;
+    for _ in 0..(world.width*world.length / scarcity) {
+        let x = rng.random_range(1..world.width-1);
+        let y = rng.random_range(1..world.length-1);
         
-        let mut spawn_z = 0;
-        for z in (-3..3).rev() {
-            if let Some(tile) = tilehash.hash.get(&Position { x, y, z }) {
-                if !tile.is_wall() {
-                    spawn_z = z;
-                    break;
-                }
-            }
-        }
+        let spawn_z = surface_z(&tilehash, &world, x, y);
 
         let position = Position { x, y, z: spawn_z };
-        if taken_positions.contains_key(&position) { continue; }
+        if taken_positions.contains_key(&position) || liquids.depth(&position) > 0.0 { continue; }
         taken_positions.insert(position, 1);
         
         if biome.objects.is_empty() { continue; }
         let object_type = biome.objects[rng.random_range(0..biome.objects.len())];
 
         commands
-            .spawn((
-                Sprite {
-                    image: sprite_sheet.handle.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: sprite_sheet.layout.clone(),
-                        index: object_type.sprite_index(),
-                    }),
-                    ..default()
-                },
-                position.to_transform(),
-            ))
+            .spawn(position.to_transform())
             .insert(position)
             .insert(Object { itemtype: object_type, ..default() })
             .insert(object_type.material_properties())
             .insert(Visibility::default());
     }
+
+    // Loose ore lies on cave floors next to the veins it broke off from.
+    for x in 1..world.width-1 {
+        for y in 1..world.length-1 {
+            for z in world.z_levels() {
+                let position = Position { x, y, z };
+                if tilehash.hash.get(&position) != Some(&TileType::CaveFloor) { continue; }
+                let ore = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, -1)].iter()
+                    .find_map(|(dx, dy, dz)| tilehash.hash.get(&Position { x: x + dx, y: y + dy, z: z + dz }).and_then(|t| t.ore_item()));
+                let Some(ore) = ore else { continue; };
+                if taken_positions.contains_key(&position) || !rng.random_bool(0.3) { continue; }
+                taken_positions.insert(position, 1);
+
+                commands
+                    .spawn(position.to_transform())
+                    .insert(position)
+                    .insert(Object { itemtype: ore, ..default() })
+                    .insert(ore.material_properties())
+                    .insert(Visibility::default());
+            }
+        }
+    }
+}
+
+/// The highest standable level of a column, or 0 if there is none.
+pub fn surface_z(tilehash: &TileHash, world: &WorldConfig, x: i32, y: i32) -> i32 {
+    world.z_levels().rev()
+        .find(|z| tilehash.hash.get(&Position { x, y, z: *z }).is_some_and(|tile| !tile.is_wall()))
+        .unwrap_or(0)
 }
 
 pub fn text_test(_commands: Commands) {}
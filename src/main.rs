@@ -5,14 +5,35 @@ mod core;
 mod simulation;
 mod rendering;
 mod initializations;
+mod headless;
+
+fn main() -> AppExit {
+    // `--seed N` fixes the world seed, so a run can be reproduced.
+    // `--width N`, `--length N`, `--min-z N` and `--max-z N` set the size of the generated world.
+    // `--headless [TICKS]` runs the simulation without a window and exits after TICKS ticks.
+    let args: Vec<String> = std::env::args().collect();
+    let sim_rng = arg_value(&args, "--seed").map(SimRng::new).unwrap_or_default();
+    let defaults = WorldConfig::default();
+    let world = WorldConfig {
+        width: arg_value(&args, "--width").unwrap_or(defaults.width).max(3),
+        length: arg_value(&args, "--length").unwrap_or(defaults.length).max(3),
+        min_z: arg_value(&args, "--min-z").unwrap_or(defaults.min_z),
+        max_z: arg_value(&args, "--max-z").unwrap_or(defaults.max_z),
+    };
+    let world = WorldConfig { max_z: world.max_z.max(world.min_z), ..world };
+    if let Some(i) = args.iter().position(|a| a == "--headless") {
+        let ticks = args.get(i + 1).and_then(|t| t.parse().ok()).unwrap_or(headless::DEFAULT_HEADLESS_TICKS);
+        return headless::run_headless(ticks, sim_rng, world);
+    }
 
-fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .insert_resource(CurrentDisplayZ { z: 0 })
+        .insert_resource(sim_rng)
+        .insert_resource(world)
+        .insert_resource(CurrentDisplayZ { z: world.clamp_z(0) })
         .add_plugins((
-             initializations::BiomePlugin,
-             initializations::StartupPlugin,
+             simulation::SimulationPlugin,
+             initializations::SavePlugin,
              rendering::CameraPlugin,
         ))
         .add_systems(
@@ -25,10 +46,7 @@ fn main() {
         )
         .insert_resource(SelectedObjectInformation::default())
         .insert_resource(InfoPanelInformation::default())
-        .init_resource::<TileHash>()
-        .init_resource::<TileEnvHash>()
         .init_resource::<Dragging>()
-        .init_resource::<GameSpeed>()
         .init_resource::<CurrentDisplayZ>()
         .init_resource::<VisualizationMode>()
         .init_resource::<UniversalMeshAssets>()
@@ -39,39 +57,23 @@ fn main() {
         })
         .add_systems(
             Startup, (
-                initializations::generate_map, 
                 setup_camera, 
                 rendering::text_test, 
                 initializations::set_window_title, 
                 initializations::set_window_maximized
             )
         )
-        .init_state::<GameState>()
         .add_plugins((
             rendering::MainMenusPlugin, 
             rendering::SelectionPlugin,
-            simulation::monstergenerator_system::MonsterGeneratorPlugin,
-            simulation::seasons::SeasonsPlugin,
-            simulation::needs::NeedsPlugin,
             rendering::GameUiPlugin,
-        ))
-        .add_plugins((
             rendering::InfoPanelPlugin,
             rendering::FeedbackPlugin,
-            simulation::thinking_system::ThinkingPlugin,
-            simulation::action_system::ActionPlugin,
-            simulation::task_system::TaskPlugin,
-            simulation::reproduction_system::ReproductionPlugin,
-            simulation::spoilage_system::SpoilagePlugin,
             rendering::ClickPlugin,
+            rendering::BuildPlugin,
+            rendering::SpriteSyncPlugin,
             rendering::VisibilityPlugin
         ))
-        .add_systems(
-            FixedUpdate, (
-                remove_bad_positions,
-                simulation::namegiving_system::namegiving_system,
-            ).run_if(in_state(GameState::InGame))
-        )
         .add_systems(
             Update, (
                 rendering::status_display_system
@@ -84,7 +86,12 @@ fn main() {
             ).run_if(in_state(GameState::InGame))
         )
         .add_systems(Update, rendering::keyboard_input)
-        .run();
+        .run()
+}
+
+fn arg_value<T: std::str::FromStr>(args: &[String], flag: &str) -> Option<T> {
+    let i = args.iter().position(|a| a == flag)?;
+    args.get(i + 1)?.parse().ok()
 }
 
 fn setup_camera(mut commands: Commands) {
@@ -93,19 +100,3 @@ fn setup_camera(mut commands: Commands) {
         Transform::from_xyz(TILE_SIZE * 19.0, TILE_SIZE * 11.0, 100.0),
     ));
 }
-
-fn remove_bad_positions(
-    mut commands: Commands,
-    query: Query<(Entity, &Position), Without<MapTile>>,
-    tiletypes: Res<TileHash>,
-) {
-    for (entity, position) in query.iter() {
-        if tiletypes.hash.contains_key(position) {
-            if tiletypes.hash.get(position).unwrap().is_wall() {
-                commands.entity(entity).despawn();
-            }
-        } else {
-            commands.entity(entity).despawn();
-        }
-    }
-}
@@ -6,6 +6,8 @@ mod click;
 pub use click::*;
 mod input;
 pub use input::*;
+mod build;
+pub use build::*;
 pub mod pause;
 mod main_menu;
 pub use main_menu::*;
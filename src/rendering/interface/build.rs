@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use crate::initializations::spawn_map_tile;
+use crate::rendering::selection_systems::SelectionEvent;
+use crate::simulation::pathfinding::is_standable;
+
+pub struct BuildPlugin;
+
+impl Plugin for BuildPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            build_designation_system.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused)))
+        );
+    }
+}
+
+/// Places the connector or furniture picked in the Build menu on every tile the player clicks
+/// or drags over, on the currently displayed Z level.
+#[allow(clippy::too_many_arguments)]
+pub fn build_designation_system(
+    mut commands: Commands,
+    mut selection_events: MessageReader<SelectionEvent>,
+    menu_state: Res<MenuState>,
+    dragging: Res<Dragging>,
+    current_z: Res<CurrentDisplayZ>,
+    buttons: Query<&Interaction, With<Button>>,
+    mut tiles: Query<(&Position, &mut TileType, &mut MaterialProperties), With<MapTile>>,
+    mut tiletypes: ResMut<TileHash>,
+    mut tileenvs: ResMut<TileEnvHash>,
+    index: Res<SpatialIndex>,
+    objects: Query<(), With<Object>>,
+) {
+    // Clicking a HUD button also reaches the map; ignore those clicks.
+    let over_ui = buttons.iter().any(|i| *i != Interaction::None);
+    for event in selection_events.read() {
+        if menu_state.state != MenuStates::Build || over_ui { continue; }
+        if event.selected_type != SelectableType::Tile { continue; }
+        let Some(mut position) = event.selected_position else { continue; };
+        position.z = current_z.z;
+        let Some(tile_type) = dragging.tile_type else {
+            // Furniture goes on open floor, one piece to a tile.
+            let occupied = index.at(&position).into_iter().any(|e| objects.contains(e));
+            if !is_standable(&tiletypes, &position) || occupied { continue; }
+            let item = dragging.item_type;
+            commands.spawn(position.to_transform())
+                .insert(position)
+                .insert(Object { itemtype: item, ..default() })
+                .insert(item.material_properties())
+                .insert(Visibility::default());
+            continue;
+        };
+
+        if let Some(existing) = tiletypes.hash.get(&position) {
+            if *existing == tile_type || existing.is_wall() { continue; }
+        }
+        tiletypes.hash.insert(position, tile_type);
+
+        if let Some((_, mut existing_type, mut material)) = tiles.iter_mut().find(|(p, ..)| **p == position) {
+            *existing_type = tile_type;
+            *material = tile_type.material_properties();
+        } else {
+            // Building into open air, e.g. the top of a staircase.
+            let env_data = tileenvs.hash.get(&Position { z: position.z - 1, ..position }).cloned().unwrap_or_default();
+            tileenvs.hash.insert(position, env_data.clone());
+            spawn_map_tile(&mut commands, position, tile_type, env_data);
+        }
+    }
+}
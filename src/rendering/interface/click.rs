@@ -106,7 +106,8 @@ pub fn object_finder_system(
     mut commands: Commands,
     mut event: MessageReader<ObjectFinderEvent>,
     mut selected_object: ResMut<SelectedObjectInformation>,
-    objects: Query<(Entity, &Position, Option<&MapTile>)>,
+    index: Res<SpatialIndex>,
+    world: Res<WorldConfig>,
     already_clicked: Query<Entity, With<ClickedOn>>,
     current_z: Res<CurrentDisplayZ>,
 ) {
@@ -118,27 +119,12 @@ pub fn object_finder_system(
             commands.entity(entity).remove::<ClickedOn>();
         }
 
-        let mut found_entity = None;
-        let mut found_tile = None;
+        // Units are visible at or below the current layer, so take the highest one there;
+        // otherwise fall back to the tile on the current layer.
+        let found_entity = (world.min_z..=current_z.z).rev()
+            .find_map(|z| index.at(&Position { x: e.position.x, y: e.position.y, z }).last().copied());
+        let found_tile = index.tile(&Position { x: e.position.x, y: e.position.y, z: current_z.z });
 
-        // Find the "best" object at this coordinate.
-        // We look for units on ANY layer first, then the specific layer tile.
-        for (entity, position, is_tile) in objects.iter() {
-            if position.x == e.position.x && position.y == e.position.y {
-                if is_tile.is_some() {
-                    // Only match tiles on current display layer
-                    if position.z == current_z.z {
-                        found_tile = Some(entity);
-                    }
-                } else {
-                    // Match units if they are at or below current layer (visible)
-                    if position.z <= current_z.z {
-                        found_entity = Some(entity);
-                    }
-                }
-            }
-        }
-        
         let target = found_entity.or(found_tile);
         if let Some(entity) = target {
             println!("Selected entity: {:?}", entity);
@@ -6,6 +6,9 @@ pub struct MainHudRoot;
 #[derive(Component)]
 pub struct TopBar;
 
+#[derive(Component)]
+pub struct TopBarText;
+
 #[derive(Component)]
 pub struct BottomBar;
 
@@ -21,8 +24,12 @@ pub struct CommandButton {
     pub selectable: Option<SelectableType>,
     pub zone: Option<ZoneType>,
     pub item: Option<ItemType>,
+    pub tile: Option<TileType>,
 }
 
+/// A bottom bar button: its label, then the `CommandButton` fields it carries.
+type MenuButton = (String, Option<MenuStates>, Option<SelectableType>, Option<ZoneType>, Option<ItemType>, Option<TileType>);
+
 pub struct GameUiPlugin;
 
 impl Plugin for GameUiPlugin {
@@ -38,6 +45,7 @@ impl Plugin for GameUiPlugin {
                 game_ui_click.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused))),
                 hud_button_interaction.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused))),
                 update_hud_on_state_change.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused))),
+                top_bar_system.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused))),
             )
         );
     }
@@ -70,7 +78,7 @@ pub fn initialize_game_ui(
             BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.8)),
             TopBar,
         )).with_children(|top| {
-            top.spawn(Text::new("Observation Ark - Colony v0.1.1"));
+            top.spawn((Text::new("Observation Ark - Colony v0.1.1"), TopBarText));
         });
 
         // BOTTOM BAR
@@ -115,6 +123,20 @@ pub fn initialize_game_ui(
     });
 }
 
+pub fn top_bar_system(
+    sim_rng: Res<SimRng>,
+    clock: Res<SimClock>,
+    game_speed: Res<GameSpeed>,
+    gamestate: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<TopBarText>>,
+) {
+    let speed = if *gamestate.get() == GameState::Paused { "Paused" } else { game_speed.label() };
+    let line = format!("Observation Ark - Colony v0.1.1 | {} | {} | Seed: {}", clock.date(), speed, sim_rng.seed);
+    for mut text in texts.iter_mut() {
+        if text.0 != line { text.0 = line.clone(); }
+    }
+}
+
 pub fn update_hud_on_state_change(
     menu_state: Res<MenuState>,
     mut commands: Commands,
@@ -131,13 +153,23 @@ pub fn update_hud_on_state_change(
             let text_font = TextFont { font: font.0.clone(), font_size: 16.0, ..default() };
             
             // Build buttons based on menu_state.state
-            let buttons: Vec<(String, Option<MenuStates>, Option<SelectableType>, Option<ZoneType>, Option<ItemType>)> = match menu_state.state {
-                MenuStates::Home => vec![],
+            let buttons: Vec<MenuButton> = match menu_state.state {
+                MenuStates::Home => vec![
+                    ("Build".to_string(), Some(MenuStates::Build), None, None, None, None),
+                ],
+                MenuStates::Build => vec![
+                    ("Ramp".to_string(), None, Some(SelectableType::Tile), None, None, Some(TileType::Ramp)),
+                    ("Stair Up".to_string(), None, Some(SelectableType::Tile), None, None, Some(TileType::StairUp)),
+                    ("Stair Down".to_string(), None, Some(SelectableType::Tile), None, None, Some(TileType::StairDown)),
+                    ("Ladder".to_string(), None, Some(SelectableType::Tile), None, None, Some(TileType::Ladder)),
+                    ("Bed".to_string(), None, Some(SelectableType::Tile), None, Some(ItemType::Bed), None),
+                    ("Back".to_string(), Some(MenuStates::Home), Some(SelectableType::Nothing), None, None, None),
+                ],
                 _ => vec![],
             };
 
             commands.entity(bottom_bar_entity).with_children(|parent| {
-                for (label, m_state, s_type, z_type, i_type) in buttons {
+                for (label, m_state, s_type, z_type, i_type, t_type) in buttons {
                     parent.spawn((
                         Button,
                         Node {
@@ -154,6 +186,7 @@ pub fn update_hud_on_state_change(
                             selectable: s_type,
                             zone: z_type,
                             item: i_type,
+                            tile: t_type,
                         },
                     )).with_children(|btn| {
                         btn.spawn((Text::new(label), text_font.clone()));
@@ -188,6 +221,9 @@ pub fn hud_button_interaction(
                 if let Some(it) = button.item {
                     dragging.item_type = it;
                 }
+                if button.tile.is_some() || button.item.is_some() || button.menu_state.is_some() {
+                    dragging.tile_type = button.tile;
+                }
             }
             Interaction::Hovered => {
                 *bg_color = BackgroundColor(Color::srgba(0.4, 0.4, 0.7, 1.0));
@@ -1,5 +1,6 @@
 ﻿use crate::prelude::*;
 use crate::rendering::interface::game_ui::InspectorContent;
+use crate::simulation::utility::UtilityScores;
 
 pub struct InfoPanelPlugin;
 
@@ -81,11 +82,13 @@ pub fn update_inspector(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn info_system(
-    mut clickable: Query<(Entity, &Position, Option<&PhysicalBody>, Option<&Brain>, Option<&HasName>, Option<&Genome>, Option<&Generation>, Option<&EnvironmentalData>), With<ClickedOn>>,
+    mut clickable: Query<(Entity, &Position, Option<&PhysicalBody>, Option<&Brain>, Option<&HasName>, Option<&Genome>, Option<&Generation>, Option<&EnvironmentalData>, Option<&UtilityScores>), With<ClickedOn>>,
     mut info_panel: ResMut<InfoPanelInformation>,
+    rooms: Res<RoomHash>,
 ) {
-    if let Some((_entity, position, physical_body, brain, has_name, genome, generation, env_data)) = clickable.iter_mut().last() {
+    if let Some((_entity, position, physical_body, brain, has_name, genome, generation, env_data, utility)) = clickable.iter_mut().last() {
         let name = has_name.map(|h| h.name.clone()).unwrap_or_else(|| "Object".to_string());
         let mut info = Vec::new();
         let mut needs = Vec::new();
@@ -105,6 +108,10 @@ pub fn info_system(
             info.extend(b.info_panel());
         }
 
+        if let Some(u) = utility {
+            info.extend(u.info_panel());
+        }
+
         if let Some(gen) = generation {
              info.push(format!("Generation: {}", gen.value));
         }
@@ -116,6 +123,10 @@ pub fn info_system(
             info.push(format!("Metabolic: {:.2}", g.metabolic_efficiency));
             info.push(format!("Diet: {:.2} (0=Light, 1=Meat)", g.diet_type));
             info.push(format!("Aggression: {:.2}", g.aggression));
+            info.push(format!("Nocturnal: {:.2} (0=Day, 1=Night)", g.nocturnal));
+            info.push(format!("Senses: {:.0} tiles, Intelligence: {:.2}", g.sensory_range, g.intelligence()));
+            info.push(format!("Learning rate: {:.2}", g.learning_rate));
+            info.push(format!("Boldness: {:.2} (0=Flee, 1=Fight)", g.boldness));
         }
 
         if let Some(env) = env_data {
@@ -123,6 +134,10 @@ pub fn info_system(
             info.push(format!("Temp: {:.1} C", env.temperature));
             info.push(format!("Humidity: {:.1}%", env.humidity * 100.0));
             info.push(format!("Fertility: {:.1}%", env.fertility * 100.0));
+            match rooms.room(position) {
+                Some(room) => info.push(format!("Room #{}: {} tiles, {:.0}% open, {:.0}% insulated", room.id, room.volume, room.openness * 100.0, room.insulation * 100.0)),
+                None => info.push("Outdoors".to_string()),
+            }
         }
 
         // IMPORTANT: Bevy Resources mark themselves as changed if you just access them mutably.
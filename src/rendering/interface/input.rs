@@ -1,5 +1,6 @@
 use crate::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn keyboard_input(
     _commands: Commands,
     input: Res<ButtonInput<KeyCode>>,
@@ -8,6 +9,8 @@ pub fn keyboard_input(
     mut nextstate: ResMut<NextState<GameState>>,
     mut current_z: ResMut<CurrentDisplayZ>,
     mut viz_mode: ResMut<VisualizationMode>,
+    world: Res<WorldConfig>,
+    mut game_speed: ResMut<GameSpeed>,
 ) {
     if input.just_pressed(KeyCode::Space) {
         // Pause or Unpause.
@@ -25,6 +28,14 @@ pub fn keyboard_input(
         }
     }
 
+    // Game speed: 1, 2, 3, 4 pick 1x, 2x, 5x and Max.
+    let speed_keys = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4];
+    for (key, (speed, _)) in speed_keys.iter().zip(GameSpeed::SETTINGS.iter()) {
+        if input.just_pressed(*key) && game_speed.speed != *speed {
+            game_speed.speed = *speed;
+        }
+    }
+
     // Toggle Visualization Modes
     if input.pressed(KeyCode::KeyT) {
         *viz_mode = VisualizationMode::Temperature;
@@ -37,10 +48,10 @@ pub fn keyboard_input(
     }
 
     if input.just_pressed(KeyCode::Comma) || input.just_pressed(KeyCode::KeyQ) {
-        current_z.z = (current_z.z + 1).clamp(-3, 2);
+        current_z.z = world.clamp_z(current_z.z + 1);
     }
     if input.just_pressed(KeyCode::Period) || input.just_pressed(KeyCode::KeyE) {
-        current_z.z = (current_z.z - 1).clamp(-3, 2);
+        current_z.z = world.clamp_z(current_z.z - 1);
     }
 
     for mut transform in camera.iter_mut() {
@@ -57,7 +68,7 @@ pub fn keyboard_input(
             next_position.x += move_speed;
         }
         transform.translation = next_position;
-        if (next_position.x >= -15.0) && (next_position.x < VIEWAREA_WIDTH as f32 * MAP_WIDTH as f32) && (next_position.y >= -15.0) && (next_position.y < VIEWAREA_HEIGHT as f32 * MAP_LENGTH as f32) {
+        if (next_position.x >= -15.0) && (next_position.x < VIEWAREA_WIDTH as f32 * world.width as f32) && (next_position.y >= -15.0) && (next_position.y < VIEWAREA_HEIGHT as f32 * world.length as f32) {
             //transform.translation = next_position;
         }
         //transform.translation = next_position;
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::initializations::{read_save, LoadGameRequest, SAVE_PATH};
 
 pub struct MainMenusPlugin;
 
@@ -22,15 +23,30 @@ impl Plugin for MainMenusPlugin {
 }
 
 pub fn main_menu_button_system(
+    mut commands: Commands,
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+        (&Interaction, &mut BackgroundColor, Option<&MainMenuButtonLoadGame>),
         (Changed<Interaction>, With<Button>),
     >,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
+    for (interaction, mut color, load_button) in interaction_query.iter_mut() {
         println!("Main Menu Button Interaction: {:?}", interaction);
         match *interaction {
+            Interaction::Pressed if load_button.is_some() => {
+                info!("Loading game from {}", SAVE_PATH);
+                match read_save(SAVE_PATH) {
+                    Ok(save) => {
+                        *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
+                        commands.insert_resource(LoadGameRequest(save));
+                        next_state.set(GameState::Initializing);
+                    }
+                    Err(e) => {
+                        warn!("Could not load {}: {}", SAVE_PATH, e);
+                        *color = BackgroundColor(Color::srgb(0.75, 0.35, 0.35));
+                    }
+                }
+            }
             Interaction::Pressed => {
                 println!("START GAME BUTTON PRESSED!");
                 *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
@@ -79,24 +95,31 @@ fn open_main_menu(
             parent.spawn((Text::new("WELCOME TO".to_string()), text_font.clone(), TextColor(Color::WHITE.into())));
             parent.spawn((Text::new("COLONY".to_string()), text_font.clone(), TextColor(Color::WHITE.into())));
             parent.spawn((Text::new("Get Started".to_string()), text_font.clone(), TextColor(Color::WHITE.into()), Node { margin: UiRect::all(Val::Px(20.0)), ..default() }));
-            // Next insert a button
-            parent.spawn((
-                Button,
-                Node { 
-                    width: Val::Px(240.0), 
-                    height: Val::Px(60.0), 
-                    margin: UiRect::all(Val::Px(20.0)), 
-                    justify_content: JustifyContent::Center, 
-                    align_items: AlignItems::Center, 
-                    border: UiRect::all(Val::Px(2.0)),
-                    ..default() 
-                }, 
-                BorderColor::all(Color::WHITE),
-                BackgroundColor(Color::srgb(0.2, 0.2, 0.2))
-            ))
-            .with_children(|parent| {
-                parent.spawn((Text::new("Start Game".to_string()), text_font.clone(), TextColor(Color::WHITE.into())));
-            });
+            // Next insert the buttons
+            for (label, is_load) in [("Start Game", false), ("Load Game", true)] {
+                let mut button = parent.spawn((
+                    Button,
+                    Node { 
+                        width: Val::Px(240.0), 
+                        height: Val::Px(60.0), 
+                        margin: UiRect::all(Val::Px(20.0)), 
+                        justify_content: JustifyContent::Center, 
+                        align_items: AlignItems::Center, 
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default() 
+                    }, 
+                    BorderColor::all(Color::WHITE),
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.2))
+                ));
+                if is_load {
+                    button.insert(MainMenuButtonLoadGame);
+                } else {
+                    button.insert(MainMenuButtonStartGame);
+                }
+                button.with_children(|parent| {
+                    parent.spawn((Text::new(label.to_string()), text_font.clone(), TextColor(Color::WHITE)));
+                });
+            }
         })
         ;
 }
@@ -6,6 +6,7 @@ pub mod selection_systems;
 pub mod camera_system;
 pub mod visibility_system;
 pub mod feedback_system;
+pub mod sprite_system;
 
 pub use interface::*;
 pub use statusdisplay_system::*;
@@ -15,3 +16,4 @@ pub use selection_systems::*;
 pub use camera_system::*;
 pub use feedback_system::*;
 pub use visibility_system::*;
+pub use sprite_system::*;
@@ -28,11 +28,12 @@ pub fn names_system(
 
 pub fn update_unit_status_text(
     mut text_query: Query<(&mut Text2d, &ChildOf), With<TextName>>,
-    brain_query: Query<(&HasName, &Brain)>,
+    brain_query: Query<(&HasName, &Brain, Has<Asleep>)>,
 ) {
     for (mut text, parent) in text_query.iter_mut() {
-        if let Ok((has_name, brain)) = brain_query.get(parent.0) {
+        if let Ok((has_name, brain, asleep)) = brain_query.get(parent.0) {
             let task_text = match &brain.task {
+                _ if asleep => "Asleep".to_string(),
                 Some(task) => format!("{:?}", task),
                 None => "Idle".to_string(),
             };
@@ -44,6 +45,10 @@ pub fn update_unit_status_text(
                     AtomicAction::Consume(_) => "CON",
                     AtomicAction::Link(_, _) => "LNK",
                     AtomicAction::Scan => "SCN",
+                    AtomicAction::Drink(_) => "DRK",
+                    AtomicAction::Rest => "ZZZ",
+                    AtomicAction::Attack(_) => "ATK",
+                    AtomicAction::Throw(_) => "THR",
                 },
                 None => "...",
             };
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+// The simulation spawns units, objects and tiles without any visuals so it can run headless.
+// This plugin gives them their sprites once they show up in a windowed game.
+pub struct SpriteSyncPlugin;
+
+impl Plugin for SpriteSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            attach_sprites,
+            update_tile_sprites,
+            update_object_sprites,
+        ).chain());
+    }
+}
+
+fn sprite_from_sheet(sprite_sheet: &SpriteSheet, index: usize) -> Sprite {
+    Sprite {
+        image: sprite_sheet.handle.clone(),
+        texture_atlas: Some(TextureAtlas {
+            layout: sprite_sheet.layout.clone(),
+            index,
+        }),
+        ..default()
+    }
+}
+
+fn tile_sprite_index(tile_type: &TileType) -> usize {
+    let (tx, ty) = tile_type.get_texture_coords();
+    (tx + ty * 64) as usize
+}
+
+#[allow(clippy::type_complexity)]
+pub fn attach_sprites(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    units: Query<(Entity, &ActorType), Without<Sprite>>,
+    objects: Query<(Entity, &Object, Option<&Corpse>), Without<Sprite>>,
+    tiles: Query<(Entity, &TileType), (With<MapTile>, Without<Sprite>)>,
+    generators: Query<Entity, (With<MonsterGenerator>, Without<Sprite>)>,
+    strikes: Query<(Entity, &StrikeType), Without<Sprite>>,
+) {
+    for (entity, actor_type) in units.iter() {
+        commands.entity(entity).insert(sprite_from_sheet(&sprite_sheet, actor_type.sprite_index()));
+    }
+    for (entity, object, corpse) in objects.iter() {
+        let sprite = match corpse {
+            // Remains look like the creature did, on its back; `update_visibility` greys them.
+            Some(corpse) => Sprite {
+                flip_y: true,
+                ..sprite_from_sheet(&sprite_sheet, corpse.actor_type.sprite_index())
+            },
+            None => sprite_from_sheet(&sprite_sheet, object.itemtype.sprite_index()),
+        };
+        commands.entity(entity).insert(sprite);
+    }
+    for (entity, tile_type) in tiles.iter() {
+        commands.entity(entity).insert(sprite_from_sheet(&sprite_sheet, tile_sprite_index(tile_type)));
+    }
+    for entity in generators.iter() {
+        commands.entity(entity).insert(sprite_from_sheet(&sprite_sheet, ItemType::Statue.sprite_index()));
+    }
+    for (entity, strike) in strikes.iter() {
+        commands.entity(entity).insert(sprite_from_sheet(&sprite_sheet, strike.sprite_index()));
+    }
+}
+
+/// Keeps tile sprites in step with tiles the player (or the world) changed in place.
+#[allow(clippy::type_complexity)]
+pub fn update_tile_sprites(
+    mut tiles: Query<(&TileType, &mut Sprite), (With<MapTile>, Changed<TileType>)>,
+) {
+    for (tile_type, mut sprite) in tiles.iter_mut() {
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            atlas.index = tile_sprite_index(tile_type);
+        }
+    }
+}
+
+/// Keeps object sprites in step with objects that turned into something else, like a felled tree.
+/// Corpses keep the look of the creature they were.
+#[allow(clippy::type_complexity)]
+pub fn update_object_sprites(
+    mut objects: Query<(&Object, &mut Sprite), (Changed<Object>, Without<Corpse>)>,
+) {
+    for (object, mut sprite) in objects.iter_mut() {
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            atlas.index = object.itemtype.sprite_index();
+        }
+    }
+}
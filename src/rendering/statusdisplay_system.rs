@@ -30,7 +30,12 @@ pub fn status_display_system (
                 vec_statuses.push("TIRED".to_string());
             }
         }
-        if let Some(Task::Maintain) = brain.task {
+        if let Some(n) = &physical_body.needs_water {
+            if n.current < 5.0 {
+                vec_statuses.push("THIRSTY".to_string());
+            }
+        }
+        if brain.task == Some(Task::Maintain) || brain.action == Some(AtomicAction::Rest) {
             vec_statuses.push("ZZZ...".to_string());
         }
         // for child in children {
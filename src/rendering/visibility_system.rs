@@ -12,9 +12,38 @@ impl Plugin for VisibilityPlugin {
     }
 }
 
+// How many levels below the displayed one stay visible, fading out with depth.
+const VIEW_DEPTH: i32 = 3;
+
+// How bright the darkest hour of the night still leaves the map.
+const NIGHT_LIGHT: f32 = 0.35;
+
+// How much darker corpses are drawn than the living, drained of colour.
+const CORPSE_SHADE: f32 = 0.5;
+
+/// Darkens a colour by the light level, 0..1.
+fn shade(color: Color, light: f32) -> Color {
+    let c = color.to_srgba();
+    Color::srgba(c.red * light, c.green * light, c.blue * light, c.alpha)
+}
+
+/// Blends a tile's colour towards blue the deeper the water standing on it.
+fn water_tint(color: Color, depth: f32) -> Color {
+    let depth = depth.clamp(0.0, 1.0) * 0.8;
+    let c = color.to_srgba();
+    Color::srgb(
+        c.red * (1.0 - depth) + 0.15 * depth,
+        c.green * (1.0 - depth) + 0.35 * depth,
+        c.blue * (1.0 - depth) + 0.85 * depth,
+    )
+}
+
 pub fn update_visibility(
     current_z: Res<CurrentDisplayZ>,
+    world: Res<WorldConfig>,
     viz_mode: Res<VisualizationMode>,
+    liquids: Res<LiquidHash>,
+    clock: Res<SimClock>,
     mut query: Query<(
         &Position, 
         &mut Visibility, 
@@ -22,10 +51,15 @@ pub fn update_visibility(
         Option<&EnvironmentalData>, 
         Option<&MapTile>,
         Option<&Genome>,
-        Option<&mut Transform>
+        Option<&mut Transform>,
+        Has<Corpse>,
     )>,
 ) {
-    for (position, mut visibility, sprite, env_data, is_map_tile, genome, transform) in query.iter_mut() {
+    // A shallow world shows all of its levels at once.
+    let view_depth = VIEW_DEPTH.min(world.max_z - world.min_z) as f32;
+    let light = NIGHT_LIGHT + (1.0 - NIGHT_LIGHT) * clock.daylight();
+    for (position, mut visibility, sprite, env_data, is_map_tile, genome, transform, corpse) in query.iter_mut() {
+        let light = if corpse { light * CORPSE_SHADE } else { light };
         if position.z > current_z.z {
             *visibility = Visibility::Hidden;
         } else if position.z == current_z.z {
@@ -60,6 +94,7 @@ pub fn update_visibility(
                                     1.0 + fertility * 0.1,
                                     1.0 - fertility * 0.2
                                 );
+                                base_color = water_tint(base_color, liquids.depth(position));
                             },
                             VisualizationMode::Temperature => {
                                 let temp = (env.temperature / 40.0 + 0.5).clamp(0.0, 1.0);
@@ -76,7 +111,7 @@ pub fn update_visibility(
                         }
                     }
                 }
-                s.color = base_color;
+                s.color = shade(base_color, light);
 
                 // Scaling Sync
                 if let (Some(g), Some(mut t)) = (genome, transform) {
@@ -86,12 +121,12 @@ pub fn update_visibility(
         } else {
             // Below current level (Dimming + Perspective Scale)
             let diff = (current_z.z - position.z) as f32;
-            if diff > 3.0 {
+            if diff > view_depth {
                 *visibility = Visibility::Hidden;
             } else {
                 *visibility = Visibility::Visible;
                 if let Some(mut s) = sprite {
-                    let dim = 1.0 - (diff * 0.25);
+                    let dim = (1.0 - (diff * 0.25)) * light;
                     let perspective_scale = 1.0 - (diff * 0.1);
                     
                     if let Some(mut t) = transform {
@@ -102,11 +137,11 @@ pub fn update_visibility(
                     if is_map_tile.is_some() && *viz_mode == VisualizationMode::Normal {
                         if let Some(env) = env_data {
                             let fertility = env.fertility.clamp(0.0, 1.0);
-                            s.color = Color::srgb(
+                            s.color = water_tint(Color::srgb(
                                 dim * (1.0 - fertility * 0.3),
                                 dim * (1.0 + fertility * 0.1),
                                 dim * (1.0 - fertility * 0.3)
-                            );
+                            ), liquids.depth(position) * dim);
                         } else {
                             s.color = Color::srgb(dim, dim, dim);
                         }
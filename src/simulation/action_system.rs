@@ -1,4 +1,12 @@
 use crate::prelude::*;
+use crate::simulation::goap::strength;
+use crate::simulation::liquids::{is_drinkable, within_reach};
+use crate::simulation::decomposition::bite;
+
+// Water need restored by one drink.
+const DRINK_AMOUNT: f32 = 40.0;
+// Blows against a sleeping unit land this much harder.
+pub const ASLEEP_DAMAGE: f32 = 2.0;
 
 pub struct ActionPlugin;
 
@@ -8,14 +16,19 @@ impl Plugin for ActionPlugin {
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn action_processor_system(
     mut commands: Commands,
-    mut actors: Query<(Entity, &mut Brain, &mut Position, &mut Transform, Option<&Genome>)>,
+    mut actors: Query<(Entity, &mut Brain, &mut Position, &mut Transform, Option<&Genome>, Option<&mut Pathing>)>,
     mut physics: Query<(Option<&mut PhysicalBody>, &MaterialProperties)>,
     q_tool_physics: Query<&MaterialProperties, Without<Brain>>,
     q_children: Query<&Children>,
+    q_objects: Query<&Object>,
+    q_asleep: Query<(), With<Asleep>>,
+    tiletypes: Res<TileHash>,
+    liquids: Res<LiquidHash>,
 ) {
-    for (entity, mut brain, mut pos, mut transform, _genome) in actors.iter_mut() {
+    for (entity, mut brain, mut pos, mut transform, genome, pathing) in actors.iter_mut() {
         if brain.action.is_none() && !brain.action_queue.is_empty() {
             brain.action = Some(brain.action_queue.remove(0));
         }
@@ -23,19 +36,30 @@ pub fn action_processor_system(
         if let Some(action) = brain.action {
             match action {
                 AtomicAction::Move(dest) => {
-                    let mut reached = true;
-                    // Move 1 unit towards destination
-                    if pos.x < dest.x { pos.x += 1; reached = false; }
-                    else if pos.x > dest.x { pos.x -= 1; reached = false; }
-                    if pos.y < dest.y { pos.y += 1; reached = false; }
-                    else if pos.y > dest.y { pos.y -= 1; reached = false; }
-                    if pos.z < dest.z { pos.z += 1; reached = false; }
-                    else if pos.z > dest.z { pos.z -= 1; reached = false; }
-                    
-                    if reached {
-                        brain.action = None;
+                    // The pathfinding system plans the route; we just walk it one tile per tick.
+                    match pathing {
+                        Some(mut pathing) if pathing.destination == dest || pathing.moving_target => {
+                            if pathing.unreachable {
+                                brain.action = None;
+                                brain.action_queue.clear();
+                                // Stop planning around things it can't get to.
+                                brain.memory.retain(|memory| match memory {
+                                    Memory::Seen { position, .. } | Memory::Water { position, .. } => *position != dest,
+                                    Memory::Learned { .. } => true,
+                                });
+                                commands.entity(entity).remove::<Pathing>();
+                            } else if pathing.path.is_empty() {
+                                brain.action = None;
+                                commands.entity(entity).remove::<Pathing>();
+                            } else {
+                                *pos = pathing.path.remove(0);
+                                *transform = pos.to_transform();
+                            }
+                        },
+                        _ => {
+                            commands.entity(entity).insert(Pathing { destination: dest, ..default() });
+                        }
                     }
-                    *transform = pos.to_transform();
                 },
                 AtomicAction::ApplyForce(target_entity, base_force) => {
                     let mut effective_force = base_force;
@@ -45,13 +69,16 @@ pub fn action_processor_system(
                         for child in children.iter() {
                             if let Ok(tool_mat) = q_tool_physics.get(child) {
                                 // A tool's hardness and mass contribute to the force of a strike
-                                effective_force += (tool_mat.hardness * 0.5) + (tool_mat.mass * 0.1);
+                                effective_force += tool_mat.striking_bonus();
                             }
                         }
                     }
 
                     if let Ok((target_body, material)) = physics.get_mut(target_entity) {
-                        let damage = (effective_force - material.hardness).max(0.1);
+                        let mut damage = (effective_force - material.hardness).max(0.1);
+                        if q_asleep.contains(target_entity) {
+                            damage *= ASLEEP_DAMAGE;
+                        }
                         
                         // Add visual feedback to target
                         commands.entity(target_entity).insert(VisualFeedback {
@@ -61,12 +88,22 @@ pub fn action_processor_system(
                         });
 
                         if let Some(mut b) = target_body {
-                            // Target is biological/has health
+                            // Target is biological/has health, and now knows who is after it
                             b.health -= damage;
+                            b.danger = Some(Danger { danger_type: DangerType::Attacked, danger_source: Some(entity) });
                         } else {
-                            // Target is inanimate - despawn if force overcomes toughness
+                            // Target is inanimate - it breaks if force overcomes toughness,
+                            // leaving its remains (a felled tree becomes a log) or nothing.
                             if damage > material.toughness {
-                                commands.entity(target_entity).despawn();
+                                let object = q_objects.get(target_entity).ok();
+                                match object.and_then(|o| o.itemtype.remains().map(|r| (o, r))) {
+                                    Some((object, remains)) => {
+                                        commands.entity(target_entity)
+                                            .insert((Object { itemtype: remains, ..object.clone() }, remains.material_properties()))
+                                            .remove::<Plant>();
+                                    }
+                                    None => commands.entity(target_entity).despawn(),
+                                }
                             }
                         }
                     }
@@ -74,15 +111,31 @@ pub fn action_processor_system(
                 },
                 AtomicAction::Consume(target_entity) => {
                     // We need both actor's body and target's body
-                    if let Ok([(Some(mut actor_body), _), (target_body_opt, target_material)]) = physics.get_many_mut([entity, target_entity]) {
+                    // Inanimate things have to be soft enough to bite through; tougher ones must be broken first.
+                    let too_tough = physics.get(target_entity)
+                        .is_ok_and(|(body, material)| body.is_none() && material.toughness > strength(genome));
+                    if too_tough {
+                        info!("Actor {:?} can't bite through {:?}", entity, target_entity);
+                    } else if q_objects.get(target_entity).is_ok_and(|o| o.itemtype == ItemType::Corpse) {
+                        // Scavenging: a bite at a time, worth more the more of a meat eater the unit is.
+                        if let Ok([(Some(mut actor_body), _), (_, meat)]) = physics.get_many_mut([entity, target_entity]) {
+                            let (energy, left) = bite(meat);
+                            let gained = energy * genome.map_or(1.0, |g| g.diet_type);
+                            actor_body.energy_storage = (actor_body.energy_storage + gained).min(actor_body.energy_max);
+                            match left {
+                                Some(left) => { commands.entity(target_entity).insert(left); }
+                                None => commands.entity(target_entity).despawn(),
+                            }
+                        }
+                    } else if let Ok([(Some(mut actor_body), _), (target_body_opt, target_material)]) = physics.get_many_mut([entity, target_entity]) {
                         let energy_to_take = 5.0; // Base value
                         let actual_energy = energy_to_take * target_material.energy_density;
                         
                         if let Some(mut b) = target_body_opt {
                             b.energy_storage -= energy_to_take;
                             if b.energy_storage <= 0.0 {
-                                // Consumed it completely
-                                commands.entity(target_entity).despawn();
+                                // Drained it completely
+                                commands.entity(target_entity).insert(Dying);
                             }
                         } else {
                             // Consuming inanimate material
@@ -100,11 +153,95 @@ pub fn action_processor_system(
                     commands.entity(child).insert(Visibility::Hidden);
                     brain.action = None;
                 },
-                AtomicAction::Scan => {
-                    // Memory populating logic
+                AtomicAction::Drink(water) => {
+                    if !is_drinkable(&tiletypes, &liquids, &water) {
+                        // Dried up since it was seen.
+                        brain.memory.retain(|memory| !matches!(memory, Memory::Water { position, .. } if *position == water));
+                    } else if !within_reach(&pos, &water) {
+                        info!("Actor {:?} is too far from the water at {:?} to drink", entity, water);
+                    } else if let Ok((Some(mut body), _)) = physics.get_mut(entity) {
+                        if let Some(need) = body.needs_water.as_mut() {
+                            need.current = (need.current + DRINK_AMOUNT).min(need.max);
+                        }
+                    }
                     brain.action = None;
+                },
+                AtomicAction::Scan => {
+                    // Carried out, and finished, by the perception system.
+                }
+                AtomicAction::Rest => {
+                    // Carried out, and finished, by the sleep system.
                 }
+                AtomicAction::Attack(_) | AtomicAction::Throw(_) => {
+                    // Carried out, and finished, by the combat system.
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::initializations::{generate_map, starting_biome};
+    use crate::simulation::pathfinding::is_standable;
+    use crate::simulation::perception::scan_system;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// A generated world with a lake sunk into its basin, and a dry bank above it where the
+    /// only water in reach is the lake one level down.
+    fn world_with_lake() -> (World, Position) {
+        for seed in 0..50 {
+            let mut world = World::new();
+            world.insert_resource(starting_biome());
+            world.insert_resource(WorldConfig::default());
+            world.init_resource::<TileHash>();
+            world.init_resource::<TileEnvHash>();
+            world.init_resource::<LiquidHash>();
+            world.insert_resource(SimRng::new(seed));
+            world.run_system_once(generate_map).unwrap();
+
+            let tiletypes = world.resource::<TileHash>();
+            let liquids = world.resource::<LiquidHash>();
+            let dry = |p: &Position| !is_drinkable(tiletypes, liquids, p);
+            let bank = liquids.hash.keys()
+                .filter(|water| is_drinkable(tiletypes, liquids, water))
+                .flat_map(|water| (-1..=1).flat_map(move |dx| (-1..=1).map(move |dy| Position { x: water.x + dx, y: water.y + dy, z: water.z + 1 })))
+                .find(|bank| is_standable(tiletypes, bank)
+                    && (-1..=1).all(|dx| (-1..=1).all(|dy| dry(&Position { x: bank.x + dx, y: bank.y + dy, z: bank.z }))));
+            if let Some(bank) = bank {
+                return (world, bank);
             }
         }
+        panic!("no generated world had a lake with a dry bank");
+    }
+
+    #[test]
+    fn units_drink_from_a_lake_below_the_bank() {
+        let (mut world, bank) = world_with_lake();
+        world.init_resource::<SpatialIndex>();
+        world.init_resource::<SimClock>();
+        let body = PhysicalBody { needs_water: Some(Need { current: 0.0, max: 100.0, ..default() }), ..default() };
+        let unit = world.spawn((
+            bank,
+            bank.to_transform(),
+            Brain { action: Some(AtomicAction::Scan), ..default() },
+            body,
+            MaterialProperties::default(),
+        )).id();
+
+        world.run_system_once(scan_system).unwrap();
+        let water = world.get::<Brain>(unit).unwrap().memory.iter()
+            .find_map(|memory| match memory {
+                Memory::Water { position, .. } if within_reach(&bank, position) => Some(*position),
+                _ => None,
+            })
+            .expect("the unit sees the lake below it");
+        assert_eq!(water.z, bank.z - 1);
+
+        world.get_mut::<Brain>(unit).unwrap().action = Some(AtomicAction::Drink(water));
+        world.run_system_once(action_processor_system).unwrap();
+        let drunk = world.get::<PhysicalBody>(unit).unwrap().needs_water.as_ref().unwrap().current;
+        assert_eq!(drunk, DRINK_AMOUNT);
     }
 }
@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+// Simulation ticks per second at 1x speed.
+pub const TICKS_PER_SECOND: f64 = 64.0;
+
+// Drives the simulation clock. Each FixedUpdate step is one tick; the game speed scales
+// virtual time, so faster speeds simply run more ticks per frame.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<SimClock>()
+        .init_resource::<GameSpeed>()
+        .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+        .add_systems(FixedPreUpdate, advance_clock.run_if(in_state(GameState::InGame)))
+        .add_systems(Update, apply_game_speed.run_if(resource_changed::<GameSpeed>));
+    }
+}
+
+/// Run condition for systems that run once every `period` simulation ticks.
+pub fn every_ticks(period: u64) -> impl Fn(Res<SimClock>) -> bool + Clone {
+    move |clock: Res<SimClock>| clock.tick.is_multiple_of(period)
+}
+
+pub fn advance_clock(
+    mut clock: ResMut<SimClock>,
+) {
+    clock.tick += 1;
+}
+
+pub fn apply_game_speed(
+    speed: Res<GameSpeed>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.set_relative_speed(speed.speed);
+}
@@ -1,12 +1,32 @@
-﻿use crate::prelude::*;
+use crate::prelude::*;
+use crate::simulation::decomposition::corpse_material;
 
+/// Whatever was brought to no health, or starved, this tick leaves a corpse where it fell
+/// and drops what it was carrying there.
+#[allow(clippy::type_complexity)]
 pub fn death_system(
     mut commands: Commands,
-    mut entities: Query<(Entity, &mut Brain, &mut PhysicalBody, &Position), With<Dying>>,
+    entities: Query<(Entity, &Position, &PhysicalBody, &MaterialProperties, Option<&ActorType>, Option<&HasName>, Option<&Children>), With<Dying>>,
+    carried: Query<(), With<Object>>,
 ) {
-    for (entity, mut _brain, mut _physical_body, _position) in entities.iter_mut() {
+    for (entity, position, body, material, actor_type, name, children) in entities.iter() {
+        info!("{} died", name.map_or("Something", |n| n.name.as_str()));
+        let mut corpse = commands.spawn((
+            *position,
+            position.to_transform(),
+            Visibility::default(),
+            Object { itemtype: ItemType::Corpse, ..default() },
+            corpse_material(material, body),
+        ));
+        if let Some(actor_type) = actor_type {
+            corpse.insert(Corpse { actor_type: *actor_type });
+        }
+
+        for child in children.into_iter().flat_map(|c| c.iter()).filter(|child| carried.contains(*child)) {
+            commands.entity(child)
+                .remove::<ChildOf>()
+                .insert((*position, position.to_transform(), Visibility::Inherited));
+        }
         commands.entity(entity).despawn();
-        // TO DO: Make a corpse and drop loot.
     }
 }
-
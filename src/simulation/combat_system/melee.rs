@@ -1,174 +1,218 @@
 use crate::prelude::*;
+use crate::simulation::action_system::ASLEEP_DAMAGE;
+use crate::simulation::goap::strength;
+use crate::simulation::perception::{line_of_sight, DEFAULT_SENSORY_RANGE};
+use rand::Rng;
 
+// Ticks between one strike and the next.
+const STRIKE_INTERVAL: u64 = 8;
+// Seconds a hit or miss marker stays on screen.
+const STRIKE_MARKER: f32 = 0.2;
+// Chance to land a blow between evenly matched fighters, and how far each point of
+// dexterity and each level of brawling moves it.
+const BASE_HIT_CHANCE: f32 = 0.5;
+const DEXTERITY_HIT: f32 = 0.05;
+const BRAWLING_HIT: f32 = 0.005;
+// Blows this hard or harder leave a cut; the second, harder, a wound.
+const CUTTING_DAMAGE: f32 = 5.0;
+const WOUNDING_DAMAGE: f32 = 15.0;
+// Ticks a cut or wound bleeds before it closes.
+const BLEEDING_TICKS: u32 = 640;
+// Ticks between bleeds, and the health each point of severity loses every time.
+pub const BLEEDING_INTERVAL: u64 = 64;
+const BLEEDING: f32 = 0.5;
+
+/// Odds of a blow landing: the more dexterous fighter has the edge, and practised brawlers more so.
+pub fn hit_chance(attacker: &PhysicalBody, defender: &PhysicalBody) -> f32 {
+    let dexterity = (attacker.attributes.dexterity - defender.attributes.dexterity) as f32 * DEXTERITY_HIT;
+    let brawling = attacker.skillset.brawling.level() as f32 * BRAWLING_HIT;
+    (BASE_HIT_CHANCE + dexterity + brawling).clamp(0.05, 0.95)
+}
+
+/// Gives up on an attack: the unit stops chasing and goes back to thinking for itself.
+fn break_off(commands: &mut Commands, entity: Entity, brain: &mut Brain) {
+    brain.action = None;
+    brain.action_queue.clear();
+    brain.task = None;
+    commands.entity(entity).remove::<(Targeting, Pathing)>();
+}
+
+/// Carries out `AtomicAction::Attack`: the unit runs its target down along the pathfinder's
+/// route and strikes every few ticks once next to it, rolling to hit. It breaks off once the
+/// target is dead or dying, out of its sensory range or out of sight, or can't be reached.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn combat_system_melee(
     mut commands: Commands,
-    mut entities_that_might_fight: Query<(Entity, &mut Brain, &mut PhysicalBody, &Position, Option<&mut Pathing>, Option<&Targeting>, Option<&Genome>)>,
-    attackables: Query<(Entity, &Position, Option<&Genome>), With<Brain>>,
-    sprite_sheet: Res<SpriteSheet>,
+    mut fighters: Query<(Entity, &mut Brain, &PhysicalBody, &mut Position, &mut Transform, Option<&Genome>, Option<&mut Pathing>, Option<&mut Targeting>)>,
+    targets: Query<&PhysicalBody, Without<Dying>>,
+    index: Res<SpatialIndex>,
+    tiletypes: Res<TileHash>,
+    clock: Res<SimClock>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    for (e, mut brain, mut physical_body, position, pathing, targeting, genome) in entities_that_might_fight.iter_mut() {
-        if brain.task != Some(Task::Fight) && brain.task != Some(Task::Hunt) { continue; }
-        if let Some(targeting) = targeting {
-            let mut entity_found = false;
-            for (entity, target_position, _target_genome) in attackables.iter() {
-                if entity == targeting.target {
-                    entity_found = true;
-                    if position.distance(target_position) <= 1 {
-                        let sprite = Sprite::from_atlas_image(
-                            sprite_sheet.handle.clone(),
-                            TextureAtlas { layout: sprite_sheet.layout.clone(), index: StrikeType::Hit.sprite_index() },
-                        );
-                        commands.spawn((sprite, Transform::from_xyz(target_position.x as f32 * TILE_SIZE, target_position.y as f32 * TILE_SIZE, target_position.z as f32 * TILE_SIZE)))
-                            .insert(target_position.clone())
-                            .insert(target_position.to_transform_layer(1.1))
-                            .insert( TemporaryVisualElement { duration: 0.2 } );
-                        commands.entity(entity).insert(Attacked { attacker: e });
-                        if pathing.is_some() { commands.entity(e).remove::<Pathing>(); }
-                    } else {
-                        // Try to follow/hunt the entity.
-                        if pathing.is_none() {
-                            commands.entity(e).insert( Pathing { path: vec![], destination: *target_position, ..default() });
-                        } else {
-                            let mut path = pathing.unwrap();
-                            // path.destination = *target_position;
-                            path.moving_target = true;
-                            //path.path = vec![];
-                        }
-                    }
-                    break;
-                }
-            }
-            if !entity_found {
-                commands.entity(e).remove::<Targeting>();
-                brain.remotivate();
-            }
-        } else {
-            // Find a target.
-            // Humans might find a target based on if they're hunting or defending.
-            // Animals might find a target based on if they're hungry or defending.
-            // For now just find the nearest physical body and make that the target.
-            if let Some(danger) = &physical_body.danger {
-                if danger.danger_type == DangerType::Attacked {
-                    // If we're being attacked, we should attack back.
-                    // Error: What happens after you win the fight? Or if the attacker no longer exists?
-                    if let Some(danger_source) = danger.danger_source {
-                        // check if attackables contains danger_source
-                        let mut danger_source_found = false;
-                        for (entity, _target_position, _target_genome) in attackables.iter() {
-                            if entity == danger_source {
-                                danger_source_found = true;
-                                break;
-                            }
-                        }
-                        if !danger_source_found {
-                            // The danger source no longer exists. We should stop attacking.
-                            brain.remotivate();
-                            physical_body.danger = None;
-                            continue;
-                        }
-                        commands.entity(e).insert(Targeting { target: danger_source });
-                        continue;
-                    }
-                }
-            }
-            let mut closest_distance = 9999;
-            let mut closest_target = None;
-            let mut closest_position = None;
-            
-            let sensory_range = genome.map(|g| g.sensory_range).unwrap_or(15.0) as i32;
+    let rng = sim_rng.stream("combat");
+    for (entity, mut brain, body, mut position, mut transform, genome, pathing, targeting) in fighters.iter_mut() {
+        let Some(AtomicAction::Attack(target)) = brain.action else { continue };
+        if brain.task.is_none() {
+            brain.task = Some(if brain.motivation == Some(Motivation::Hunger) { Task::Hunt } else { Task::Fight });
+        }
 
-            for (attackable, attackable_position, target_genome) in attackables.iter() {
-                if attackable == e { continue; }
-                
-                let distance = position.distance(attackable_position);
-                if distance > sensory_range { continue; }
+        let range = genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32);
+        let (Ok(target_body), Some(target_at)) = (targets.get(target), index.position(target)) else {
+            break_off(&mut commands, entity, &mut brain);
+            continue;
+        };
+        if target == entity || position.distance(&target_at) > range || !line_of_sight(&tiletypes, &position, &target_at) {
+            break_off(&mut commands, entity, &mut brain);
+            continue;
+        }
 
-                // Hunting logic vs Fighting logic
-                if brain.task == Some(Task::Hunt) {
-                    if let (Some(g1), Some(g2)) = (genome, target_genome) {
-                        // Don't hunt things that are genetically too close (same species)
-                        if g1.genetic_distance(g2) < 0.2 { continue; }
-                    }
-                }
+        let adjacent = target_at.z == position.z && (target_at.x - position.x).abs() <= 1 && (target_at.y - position.y).abs() <= 1;
+        if adjacent {
+            if pathing.is_some() { commands.entity(entity).remove::<Pathing>(); }
+            let ready = targeting.as_ref().is_none_or(|t| t.target != target || clock.tick >= t.last_strike + STRIKE_INTERVAL);
+            if !ready { continue; }
+            let strike = if rng.random::<f32>() < hit_chance(body, target_body) { StrikeType::Hit } else { StrikeType::Miss };
+            commands.spawn((
+                strike,
+                target_at,
+                // Just above the creature it marks.
+                Transform::from_translation(target_at.to_transform().translation + Vec3::Z * 0.05),
+                TemporaryVisualElement { duration: STRIKE_MARKER },
+            ));
+            if strike == StrikeType::Hit {
+                commands.entity(target).insert(Attacked { attacker: entity });
+            }
+            commands.entity(entity).insert(Targeting { target, last_strike: clock.tick });
+            continue;
+        }
 
-                if distance < closest_distance {
-                    closest_distance = distance;
-                    closest_target = Some(attackable);
-                    closest_position = Some(attackable_position);
+        // Not there yet: follow the target, one tile per tick, as it moves.
+        match pathing {
+            Some(pathing) if pathing.unreachable => break_off(&mut commands, entity, &mut brain),
+            Some(mut pathing) if pathing.moving_target => {
+                if !pathing.path.is_empty() {
+                    *position = pathing.path.remove(0);
+                    *transform = position.to_transform();
                 }
             }
-            if let Some(closest_target) = closest_target {
-                commands.entity(e).insert(Targeting { target: closest_target });
-                let target_position = closest_position.unwrap();
-                commands.entity(e).insert( Pathing { path: vec![], destination: *target_position, ..default() });
-            } else {
-                // Nothing to attack. Now what?
-                brain.remotivate();
+            _ => {
+                let last_strike = targeting.filter(|t| t.target == target).map_or(0, |t| t.last_strike);
+                commands.entity(entity).insert((
+                    Targeting { target, last_strike },
+                    Pathing { destination: target_at, moving_target: true, ..default() },
+                ));
             }
         }
     }
 }
 
+/// Lands a blow: the harder the hitter and the softer the target, the more it takes off health.
+#[allow(clippy::too_many_arguments)]
 fn do_melee_damage(
     commands: &mut Commands,
-    attacker_entity: Option<Entity>,
+    attacker_entity: Entity,
     attacked_entity: Entity,
     attacker_body: &mut PhysicalBody,
     attacker_genome: Option<&Genome>,
     body2: &mut PhysicalBody,
-    _asset_server: &Res<AssetServer>
+    asleep: bool,
+    rng: &mut impl Rng,
 ) {
-    let damage = (
+    let mut damage = (
         1 +
-        (attacker_body.attributes.strength - body2.attributes.constitution).max(0).min(20) +
-        (attacker_body.skillset.brawling.level()).max(0).min(20)
-    ) as f32;
-    body2.health -= damage;
-    if body2.health <= 0.0 {
-        // Predation energy gain
-        if let Some(genome) = attacker_genome {
-            // Carnivores gain energy from kills
-            let energy_gain = (body2.energy_max * 0.5 + body2.energy_storage) * genome.diet_type;
-            attacker_body.energy_storage = (attacker_body.energy_storage + energy_gain).min(attacker_body.energy_max);
-            info!("Predator {:?} consumed target for {} energy", attacker_entity, energy_gain);
-        }
+        (attacker_body.attributes.strength - body2.attributes.constitution).clamp(0, 20) +
+        attacker_body.skillset.brawling.level().clamp(0, 20)
+    ) as f32 + strength(attacker_genome);
+    if asleep {
+        damage *= ASLEEP_DAMAGE;
+    }
+    attacker_body.skillset.brawling.experience += 1;
 
-        commands.entity(attacked_entity).despawn();
+    injure(commands, attacker_entity, attacked_entity, body2, damage, rng);
+}
+
+/// Takes `damage` off a body's health; hard blows leave cuts and wounds that go on bleeding.
+/// The body now knows who is after it, and is dying if it has no health left. Returns whether it is.
+pub fn injure(
+    commands: &mut Commands,
+    attacker_entity: Entity,
+    attacked_entity: Entity,
+    body: &mut PhysicalBody,
+    damage: f32,
+    rng: &mut impl Rng,
+) -> bool {
+    body.health -= damage;
+    if damage >= CUTTING_DAMAGE {
+        let severity = (damage / CUTTING_DAMAGE) as u32;
+        let location = [
+            AfflictionLocation::Head,
+            AfflictionLocation::LeftArm,
+            AfflictionLocation::RightArm,
+            AfflictionLocation::LeftLeg,
+            AfflictionLocation::RightLeg,
+            AfflictionLocation::Torso,
+        ][rng.random_range(0..6)].clone();
+        body.afflictions.push(Affliction {
+            location,
+            affliction_type: if damage >= WOUNDING_DAMAGE { AfflictionType::Wound } else { AfflictionType::Cut },
+            duration: BLEEDING_TICKS,
+            severity,
+            worsening: false,
+        });
+        body.injured = true;
     }
-    body2.danger = Some(Danger {
+
+    commands.entity(attacked_entity).insert(VisualFeedback {
+        shake_timer: 0.2,
+        shake_intensity: 2.0,
+        ..default()
+    });
+    body.danger = Some(Danger {
         danger_type: DangerType::Attacked,
-        danger_source: attacker_entity,
+        danger_source: Some(attacker_entity),
     });
+    if body.health <= 0.0 {
+        commands.entity(attacked_entity).insert(Dying);
+    }
+    body.health <= 0.0
 }
 
+/// Applies the blows that landed this tick, then clears them.
 pub fn attacked_entities_system(
     mut commands: Commands,
-    attacked_query: Query<(Entity, &Attacked), With<Attacked>>,
-    mut physical_bodies: Query<(Entity, &mut PhysicalBody, Option<&Genome>)>,
-    asset_server: Res<AssetServer>
+    attacked_query: Query<(Entity, &Attacked, Has<Asleep>)>,
+    mut physical_bodies: Query<(&mut PhysicalBody, Option<&Genome>), Without<Dying>>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    for (attacked_entity, attack_info) in attacked_query.iter() {
+    let rng = sim_rng.stream("combat");
+    for (attacked_entity, attack_info, asleep) in attacked_query.iter() {
         commands.entity(attacked_entity).remove::<Attacked>();
-        
-        // Use unsafe split or two-pass to get both bodies
-        let mut attacker_data: Option<(Entity, PhysicalBody, Option<Genome>)> = None;
-        for (entity, body, genome) in physical_bodies.iter() {
-            if entity == attack_info.attacker {
-                attacker_data = Some((entity, body.clone(), genome.cloned()));
-                break;
-            }
-        }
 
-        if let Some((a_entity, mut a_body, a_genome)) = attacker_data {
-            if let Ok((_v_entity, mut v_body, _v_genome)) = physical_bodies.get_mut(attacked_entity) {
-                do_melee_damage(&mut commands, Some(a_entity), attacked_entity, &mut a_body, a_genome.as_ref(), &mut v_body, &asset_server);
-                
-                // Write back attacker body changes (energy gain)
-                if let Ok((_, mut entry_body, _)) = physical_bodies.get_mut(a_entity) {
-                    *entry_body = a_body;
-                }
-            }
-        }
+        let Ok([(mut a_body, a_genome), (mut v_body, _)]) = physical_bodies.get_many_mut([attack_info.attacker, attacked_entity]) else { continue };
+        do_melee_damage(&mut commands, attack_info.attacker, attacked_entity, &mut a_body, a_genome, &mut v_body, asleep, rng);
+    }
+}
+
+/// Cuts and wounds bleed every time this runs until they close; other afflictions with a
+/// duration wear off, and ones without one are lasting.
+pub fn bleeding_system(
+    mut bodies: Query<&mut PhysicalBody>,
+) {
+    for mut body in bodies.iter_mut() {
+        if body.afflictions.iter().all(|a| a.duration == 0) { continue; }
+        let bleeding: u32 = body.afflictions.iter()
+            .filter(|a| a.duration > 0 && matches!(a.affliction_type, AfflictionType::Cut | AfflictionType::Wound))
+            .map(|a| a.severity)
+            .sum();
+        body.health -= bleeding as f32 * BLEEDING;
+        body.afflictions.retain_mut(|a| {
+            if a.duration == 0 { return true; }
+            a.duration = a.duration.saturating_sub(BLEEDING_INTERVAL as u32);
+            a.duration > 0
+        });
+        body.injured = body.afflictions.iter()
+            .any(|a| matches!(a.affliction_type, AfflictionType::Cut | AfflictionType::Wound | AfflictionType::BrokenBone));
     }
 }
 
@@ -185,4 +229,3 @@ pub fn temporary_visual_elements_system(
         }
     }
 }
-
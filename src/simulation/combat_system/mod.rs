@@ -0,0 +1,94 @@
+use crate::prelude::*;
+use crate::simulation::action_system::action_processor_system;
+use crate::simulation::clock::every_ticks;
+use crate::simulation::fear::predator_threat;
+use crate::simulation::perception::DEFAULT_SENSORY_RANGE;
+use crate::simulation::thinking_system::Hunger;
+use crate::simulation::utility::*;
+use rand::rngs::StdRng;
+
+pub mod death;
+pub mod melee;
+pub mod ranged;
+
+// How frightening a hunter has to be to another kind of creature to see it as prey.
+const PREY_THREAT: f32 = 0.2;
+
+/// Combat from the first throw to the last breath: units throw what they hold and chase down
+/// and strike what they attack, blows land or miss, wounds bleed and the dead are removed.
+/// Predators hunt.
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .register_behavior(Behavior::new(BehaviorKind::Hunt, Motivation::Hunger, |g| 150.0 * g.map_or(0.0, |g| g.weight_hunger * g.diet_type), hunt_plan)
+            .consider(Hunger, ResponseCurve::Threshold(0.3))
+            .consider(PreyNearby, ResponseCurve::Linear))
+        .add_systems(FixedUpdate, (
+            melee::combat_system_melee,
+            ranged::combat_system_ranged,
+            ranged::projectile_system,
+            melee::attacked_entities_system,
+            death::death_system,
+        ).chain()
+            .after(action_processor_system)
+            .run_if(in_state(GameState::InGame)))
+        .add_systems(FixedUpdate, melee::bleeding_system
+            .before(death::death_system)
+            .run_if(every_ticks(melee::BLEEDING_INTERVAL))
+            .run_if(in_state(GameState::InGame)))
+        .add_systems(Update, melee::temporary_visual_elements_system);
+    }
+}
+
+/// Other kinds of creature the unit remembers seeing that it is enough of a hunter to take down.
+pub fn prey(
+    memory: &[Memory],
+    genome: Option<&Genome>,
+    actor_type: Option<&ActorType>,
+    units: &Query<(&Genome, &ActorType)>,
+) -> Vec<(Entity, Position)> {
+    let Some(genome) = genome else { return Vec::new() };
+    memory.iter()
+        .filter_map(|memory| match memory {
+            Memory::Seen { entity, position, .. } => Some((*entity, *position)),
+            _ => None,
+        })
+        .filter(|(other, _)| units.get(*other).is_ok_and(|(other_genome, other_type)| {
+            Some(other_type) != actor_type && predator_threat(genome, Some(other_genome)) >= PREY_THREAT
+        }))
+        .collect()
+}
+
+/// How a unit goes after a creature: a throw first if it holds something to throw and the
+/// target is out of reach but in range, then in to fight.
+pub fn engage(ctx: &Context, target: Entity, position: Position) -> Vec<AtomicAction> {
+    let distance = ctx.position.distance(&position);
+    let can_throw = ctx.tool.as_ref().is_some_and(|tool| ranged::is_throwable(tool, ctx.genome));
+    if can_throw && distance > 1 && distance <= ranged::THROW_RANGE {
+        vec![AtomicAction::Throw(target), AtomicAction::Attack(target)]
+    } else {
+        vec![AtomicAction::Attack(target)]
+    }
+}
+
+fn nearest_prey(ctx: &Context) -> Option<(Entity, Position)> {
+    ctx.prey.iter().copied().min_by_key(|(_, p)| ctx.position.distance(p))
+}
+
+/// How close the nearest prey is, 1 when adjacent and 0 at the edge of the unit's senses.
+pub struct PreyNearby;
+
+impl Consideration for PreyNearby {
+    fn name(&self) -> &'static str { "prey nearby" }
+    fn score(&self, ctx: &Context) -> f32 {
+        let range = ctx.genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32).max(1);
+        nearest_prey(ctx).map_or(0.0, |(_, p)| 1.0 - ctx.position.distance(&p) as f32 / range as f32)
+    }
+}
+
+fn hunt_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let (prey, position) = nearest_prey(ctx)?;
+    Some(engage(ctx, prey, position))
+}
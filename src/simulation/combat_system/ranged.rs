@@ -1,12 +1,228 @@
-﻿use crate::prelude::*;
+use crate::prelude::*;
+use crate::simulation::goap::{is_tool, strength};
+use crate::simulation::pathfinding::is_standable;
+use crate::simulation::perception::line_of_sight;
+use super::melee::{hit_chance, injure};
+use rand::Rng;
 
+// Furthest a unit can throw, in tiles.
+pub const THROW_RANGE: i32 = 6;
+// Tiles a projectile crosses every tick.
+const PROJECTILE_SPEED: usize = 2;
+// How much less likely a throw is to hit for every tile to the target.
+const RANGE_PENALTY: f32 = 0.05;
+// How far from its target a miss can land, in tiles.
+const SCATTER: i32 = 2;
+
+/// Whether a thing is hard enough to hurt and light enough for the unit to throw.
+pub fn is_throwable(material: &MaterialProperties, genome: Option<&Genome>) -> bool {
+    is_tool(material) && material.mass <= strength(genome) * 2.0
+}
+
+/// What a thrown thing takes off a body's health: heavy, hard things hurt most.
+pub fn projectile_damage(material: &MaterialProperties) -> f32 {
+    material.hardness + material.mass * 0.5
+}
+
+/// Carries out `AtomicAction::Throw`: a unit holding something throwable lets fly at a target
+/// it can see within range, rolling to hit, and lets go of it as a projectile. A unit with
+/// nothing to throw, or no target in range, just moves on to its next action.
+#[allow(clippy::type_complexity)]
 pub fn combat_system_ranged(
-    _commands: Commands,
-    //mut query: Query<(&mut Brain, &mut PhysicalBody)>
+    mut commands: Commands,
+    mut throwers: Query<(Entity, &mut Brain, &PhysicalBody, &Position, Option<&Genome>, Option<&Children>)>,
+    items: Query<&MaterialProperties, (With<Object>, Without<Brain>)>,
+    targets: Query<&PhysicalBody, Without<Dying>>,
+    index: Res<SpatialIndex>,
+    tiletypes: Res<TileHash>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    // for (mut brain, mut physical_body) in query.iter_mut() {
-    //     if brain.task != Some(Task::Fight) { continue; }
-        
-    // }
+    let rng = sim_rng.stream("combat");
+    for (entity, mut brain, body, position, genome, children) in throwers.iter_mut() {
+        let Some(AtomicAction::Throw(target)) = brain.action else { continue };
+        brain.action = None;
+
+        let item = children.and_then(|c| c.iter().find(|child| items.get(*child).is_ok_and(|m| is_throwable(m, genome))));
+        let (Some(item), Ok(target_body), Some(target_at)) = (item, targets.get(target), index.position(target)) else { continue };
+        let distance = position.distance(&target_at);
+        if distance > THROW_RANGE || !line_of_sight(&tiletypes, position, &target_at) { continue; }
+
+        let hit = rng.random::<f32>() < hit_chance(body, target_body) - distance as f32 * RANGE_PENALTY;
+        let destination = if hit { target_at } else {
+            Position {
+                x: target_at.x + rng.random_range(-SCATTER..=SCATTER),
+                y: target_at.y + rng.random_range(-SCATTER..=SCATTER),
+                z: target_at.z,
+            }
+        };
+        commands.entity(item)
+            .remove::<ChildOf>()
+            .insert((*position, position.to_transform(), Visibility::Inherited, Projectile { thrower: entity, target, destination, hit, last_ground: *position }));
+    }
 }
 
+/// The next tile on the straight line from one position to another.
+fn step_towards(from: &Position, to: &Position) -> Position {
+    let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+    let steps = dx.abs().max(dy.abs()).max(dz.abs()).max(1) as f32;
+    Position {
+        x: from.x + (dx as f32 / steps).round() as i32,
+        y: from.y + (dy as f32 / steps).round() as i32,
+        z: from.z + (dz as f32 / steps).round() as i32,
+    }
+}
+
+/// Where something coming down at `position` ends up: the first ground below it, or the
+/// last ground it passed over if solid rock or the bottom of the world is in the way.
+fn landing(tiletypes: &TileHash, world: &WorldConfig, position: &Position, last_ground: Position) -> Position {
+    (world.min_z..=position.z).rev()
+        .map(|z| Position { z, ..*position })
+        .find(|p| tiletypes.hash.contains_key(p))
+        .filter(|p| is_standable(tiletypes, p))
+        .unwrap_or(last_ground)
+}
+
+/// Flies thrown things a few tiles a tick, hurting the target if the throw hit. Where a wall
+/// or the ground between levels stops them, or at the end of their flight, they fall to the
+/// ground below and lie there to be picked up again.
+#[allow(clippy::too_many_arguments)]
+pub fn projectile_system(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Projectile, &mut Position, &mut Transform, &MaterialProperties)>,
+    mut bodies: Query<&mut PhysicalBody, Without<Dying>>,
+    index: Res<SpatialIndex>,
+    tiletypes: Res<TileHash>,
+    world: Res<WorldConfig>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    let rng = sim_rng.stream("combat");
+    for (entity, mut projectile, mut position, mut transform, material) in projectiles.iter_mut() {
+        if projectile.hit {
+            if let Some(target_at) = index.position(projectile.target) {
+                projectile.destination = target_at;
+            }
+        }
+
+        let mut blocked = false;
+        for _ in 0..PROJECTILE_SPEED {
+            if *position == projectile.destination { break; }
+            let next = step_towards(&position, &projectile.destination);
+            // Open air lets it through, like sight; walls and solid ground stop it.
+            if tiletypes.hash.contains_key(&next) && !is_standable(&tiletypes, &next) {
+                blocked = true;
+                break;
+            }
+            *position = next;
+            if is_standable(&tiletypes, &position) {
+                projectile.last_ground = *position;
+            }
+        }
+        let arrived = *position == projectile.destination;
+        if !blocked && !arrived {
+            *transform = position.to_transform();
+            continue;
+        }
+
+        commands.entity(entity).remove::<Projectile>();
+        if arrived && projectile.hit && index.position(projectile.target) == Some(*position) {
+            if let Ok(mut body) = bodies.get_mut(projectile.target) {
+                injure(&mut commands, projectile.thrower, projectile.target, &mut body, projectile_damage(material), rng);
+            }
+        }
+        *position = landing(&tiletypes, &world, &position, projectile.last_ground);
+        *transform = position.to_transform();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// A slope: low ground at z 0 up to x 7, and a rise at z 1 from x 8 on.
+    fn slope() -> TileHash {
+        let mut tiletypes = TileHash::default();
+        for x in 0..14 {
+            for y in 0..9 {
+                let top = if x < 8 { 0 } else { 1 };
+                tiletypes.hash.insert(Position { x, y, z: top }, TileType::Grass);
+                tiletypes.hash.insert(Position { x, y, z: top - 1 }, TileType::Stone);
+            }
+        }
+        tiletypes
+    }
+
+    #[test]
+    fn missed_throws_come_down_on_standable_ground() {
+        let mut world = World::new();
+        world.insert_resource(slope());
+        world.insert_resource(WorldConfig::default());
+        world.insert_resource(SimRng::new(7));
+
+        let target_at = Position { x: 9, y: 4, z: 1 };
+        let mut target_body = PhysicalBody::default();
+        // Far too nimble to ever be hit.
+        target_body.attributes.dexterity = 100;
+        let target = world.spawn((target_at, target_body)).id();
+        let thrower = world.spawn((Position { x: 4, y: 4, z: 0 }, Brain::default(), PhysicalBody::default())).id();
+        let mut index = SpatialIndex::default();
+        index.insert(target, target_at);
+        world.insert_resource(index);
+
+        let rock = MaterialProperties { hardness: 6.0, toughness: 10.0, energy_density: 0.0, mass: 2.0, conductivity: 0.1 };
+        for _ in 0..20 {
+            let item = world.spawn((Object::default(), rock.clone(), ChildOf(thrower))).id();
+            world.get_mut::<Brain>(thrower).unwrap().action = Some(AtomicAction::Throw(target));
+            world.run_system_once(combat_system_ranged).unwrap();
+            assert!(world.get::<Projectile>(item).is_some_and(|p| !p.hit));
+            for _ in 0..10 {
+                world.run_system_once(projectile_system).unwrap();
+            }
+
+            assert!(world.get::<Projectile>(item).is_none());
+            let landed = *world.get::<Position>(item).expect("thrown item is still around");
+            assert!(is_standable(world.resource::<TileHash>(), &landed), "landed on {landed:?}");
+        }
+    }
+
+    #[test]
+    fn throws_down_the_slope_survive_the_flight() {
+        let mut world = World::new();
+        world.insert_resource(slope());
+        world.insert_resource(WorldConfig::default());
+        world.insert_resource(SimRng::new(7));
+
+        let target_at = Position { x: 4, y: 4, z: 0 };
+        let target = world.spawn((target_at, PhysicalBody { health: 1000.0, ..default() })).id();
+        let thrower = world.spawn((Position { x: 9, y: 4, z: 1 }, Brain::default(), PhysicalBody::default())).id();
+        let mut index = SpatialIndex::default();
+        index.insert(target, target_at);
+        world.insert_resource(index);
+
+        // Flight and the clean-up of things off the map, as they run every tick.
+        let mut tick = Schedule::default();
+        tick.add_systems((projectile_system, crate::simulation::remove_bad_positions).chain());
+
+        let rock = MaterialProperties { hardness: 6.0, toughness: 10.0, energy_density: 0.0, mass: 2.0, conductivity: 0.1 };
+        let mut hits = 0;
+        for _ in 0..20 {
+            let item = world.spawn((Object::default(), rock.clone(), ChildOf(thrower))).id();
+            world.get_mut::<Brain>(thrower).unwrap().action = Some(AtomicAction::Throw(target));
+            world.run_system_once(combat_system_ranged).unwrap();
+            let hit = world.get::<Projectile>(item).expect("the rock was thrown").hit;
+            let health = world.get::<PhysicalBody>(target).unwrap().health;
+            for _ in 0..10 {
+                tick.run(&mut world);
+            }
+
+            let landed = *world.get::<Position>(item).expect("thrown item is still around");
+            assert!(world.get::<Projectile>(item).is_none());
+            assert!(is_standable(world.resource::<TileHash>(), &landed), "landed on {landed:?}");
+            if hit {
+                hits += 1;
+                assert!(world.get::<PhysicalBody>(target).unwrap().health < health);
+            }
+        }
+        assert!(hits > 0);
+    }
+}
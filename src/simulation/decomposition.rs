@@ -0,0 +1,78 @@
+use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+
+// Share of a body's full energy store that stays in its flesh, on top of whatever it had stored.
+const MEAT_SHARE: f32 = 0.5;
+// Most energy one bite takes out of a corpse.
+const BITE_ENERGY: f32 = 10.0;
+// Ticks between rounds of decay, and the share of what is left each round takes.
+const DECAY_INTERVAL: u64 = 128;
+const DECAY_SHARE: f32 = 0.05;
+// Corpses with less energy than this left are gone, eaten or rotted away.
+const ROTTED: f32 = 1.0;
+// Fertility the ground gains for every unit of energy rotting into it, shared by the tiles around.
+const FERTILITY_PER_ENERGY: f32 = 0.005;
+
+pub struct DecompositionPlugin;
+
+impl Plugin for DecompositionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, decomposition_system
+            .run_if(every_ticks(DECAY_INTERVAL))
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// What is left of a creature: as heavy as it was, and holding its stored energy plus its flesh.
+pub fn corpse_material(material: &MaterialProperties, body: &PhysicalBody) -> MaterialProperties {
+    let mass = material.mass.max(0.1);
+    let energy = body.energy_storage.max(0.0) + body.energy_max * MEAT_SHARE;
+    MaterialProperties {
+        mass,
+        energy_density: energy / mass,
+        ..ItemType::Corpse.material_properties()
+    }
+}
+
+/// Takes a bite out of a corpse: the energy bitten off, and the corpse that is left, if any.
+pub fn bite(meat: &MaterialProperties) -> (f32, Option<MaterialProperties>) {
+    let energy = meat.mass * meat.energy_density;
+    let bitten = energy.min(BITE_ENERGY);
+    let left = (energy - bitten >= ROTTED).then(|| MaterialProperties {
+        mass: meat.mass * (1.0 - bitten / energy),
+        ..meat.clone()
+    });
+    (bitten, left)
+}
+
+/// Corpses lose a share of their flesh every round, and what rots away feeds the ground
+/// they lie on and around. Corpses with next to nothing left are gone.
+pub fn decomposition_system(
+    mut commands: Commands,
+    mut corpses: Query<(Entity, &Object, &mut MaterialProperties, &Position), Without<ChildOf>>,
+    mut tileenvs: ResMut<TileEnvHash>,
+) {
+    for (entity, object, mut material, position) in corpses.iter_mut() {
+        if object.itemtype != ItemType::Corpse { continue; }
+
+        let energy = material.mass * material.energy_density;
+        let rotted = energy < ROTTED;
+        let released = if rotted { energy } else { energy * DECAY_SHARE };
+        material.mass *= 1.0 - released / energy.max(f32::EPSILON);
+
+        let ground: Vec<Position> = (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| Position { x: position.x + dx, y: position.y + dy, z: position.z }))
+            .filter(|p| tileenvs.hash.contains_key(p))
+            .collect();
+        let share = released * FERTILITY_PER_ENERGY / ground.len().max(1) as f32;
+        for p in ground.iter() {
+            if let Some(env) = tileenvs.hash.get_mut(p) {
+                env.fertility = (env.fertility + share).min(1.0);
+            }
+        }
+
+        if rotted {
+            commands.entity(entity).despawn();
+        }
+    }
+}
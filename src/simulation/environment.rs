@@ -0,0 +1,95 @@
+use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+
+// Share of the temperature difference that crosses between two perfect conductors each step.
+const DIFFUSION_RATE: f32 = 0.15;
+// Heat rising from the bottom of the world.
+const GEOTHERMAL_TEMPERATURE: f32 = 30.0;
+const MAGMA_TEMPERATURE: f32 = 80.0;
+// How fast the sun and the ground pull tiles towards their temperature.
+const SOURCE_RATE: f32 = 0.1;
+// How fast magma and `HeatSource`s do.
+const HOT_SOURCE_RATE: f32 = 0.5;
+
+const NEIGHBOURS_3D: [(i32, i32, i32); 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+            heat_diffusion_system,
+            sync_tile_environment.after(crate::simulation::liquids::humidity_system),
+        ).chain()
+            .run_if(every_ticks(64))
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn conductivity(tiletypes: &TileHash, position: &Position) -> f32 {
+    tiletypes.hash.get(position).map_or(0.0, |t| t.material_properties().conductivity)
+}
+
+/// Whether nothing lies between this tile and the sky.
+pub fn open_to_sky(tiletypes: &TileHash, world: &WorldConfig, position: &Position) -> bool {
+    (position.z + 1..=world.max_z).all(|z| !tiletypes.hash.contains_key(&Position { z, ..*position }))
+}
+
+/// One step of heat exchange between touching tiles, scaled by the poorer conductor of each pair,
+/// followed by the heat sources: the season's sun on open ground, the hot bottom of the world, magma,
+/// and any entity with a `HeatSource`. Rooms keep out part of the change.
+pub fn heat_diffusion_system(
+    tiletypes: Res<TileHash>,
+    mut tileenvs: ResMut<TileEnvHash>,
+    rooms: Res<RoomHash>,
+    world: Res<WorldConfig>,
+    clock: Res<SimClock>,
+    sources: Query<(&Position, &HeatSource)>,
+) {
+    let sun_temperature = clock.season().surface_temperature();
+    let before: HashMap<Position, f32> = tileenvs.hash.iter().map(|(p, e)| (*p, e.temperature)).collect();
+    let mut heaters: HashMap<Position, f32> = HashMap::new();
+    for (position, source) in sources.iter() {
+        let entry = heaters.entry(*position).or_insert(source.temperature);
+        *entry = entry.max(source.temperature);
+    }
+
+    for (position, env) in tileenvs.hash.iter_mut() {
+        let temperature = before[position];
+        let own_conductivity = conductivity(&tiletypes, position);
+        let mut change = 0.0;
+        for (dx, dy, dz) in NEIGHBOURS_3D {
+            let neighbour = Position { x: position.x + dx, y: position.y + dy, z: position.z + dz };
+            if let Some(other) = before.get(&neighbour) {
+                let k = own_conductivity.min(conductivity(&tiletypes, &neighbour)) * DIFFUSION_RATE;
+                change += k * (other - temperature);
+            }
+        }
+
+        if tiletypes.hash.get(position) == Some(&TileType::Magma) {
+            change += (MAGMA_TEMPERATURE - temperature) * HOT_SOURCE_RATE;
+        } else if position.z == world.min_z {
+            change += (GEOTHERMAL_TEMPERATURE - temperature) * SOURCE_RATE;
+        } else if open_to_sky(&tiletypes, &world, position) {
+            change += (sun_temperature - temperature) * SOURCE_RATE;
+        }
+        if let Some(heat) = heaters.get(position) {
+            change += (heat - temperature) * HOT_SOURCE_RATE;
+        }
+        env.temperature = temperature + change * rooms.exposure(position);
+    }
+}
+
+/// Copies the simulated climate in `TileEnvHash` onto the map tile entities,
+/// which is what the inspector and the visualization overlays read.
+pub fn sync_tile_environment(
+    tileenvs: Res<TileEnvHash>,
+    mut tiles: Query<(&Position, &mut EnvironmentalData), With<MapTile>>,
+) {
+    for (position, mut env) in tiles.iter_mut() {
+        let Some(updated) = tileenvs.hash.get(position) else { continue };
+        if env.temperature != updated.temperature || env.humidity != updated.humidity || env.fertility != updated.fertility {
+            *env = updated.clone();
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+use crate::simulation::combat_system::engage;
+use crate::simulation::perception::DEFAULT_SENSORY_RANGE;
+use crate::simulation::utility::*;
+use rand::rngs::StdRng;
+
+// Threat from whatever attacked the unit or otherwise put it in danger.
+const DANGER_THREAT: f32 = 1.0;
+// Predators last seen longer ago than this many ticks no longer frighten.
+const THREAT_MEMORY: u64 = 256;
+// How far a fleeing unit runs when it has nowhere safe to run to.
+const FLEE_DISTANCE: f32 = 8.0;
+
+pub struct FearPlugin;
+
+impl Plugin for FearPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        // Fight or flight: the same threat drives both, split by the unit's boldness.
+        .register_behavior(Behavior::new(BehaviorKind::Flee, Motivation::Fear, |_| 250.0, flee_plan)
+            .consider(Threatened, ResponseCurve::Threshold(0.2))
+            .consider(Boldness, ResponseCurve::Inverse))
+        .register_behavior(Behavior::new(BehaviorKind::StandGround, Motivation::Fear, |_| 250.0, stand_ground_plan)
+            .consider(Threatened, ResponseCurve::Threshold(0.2))
+            .consider(Boldness, ResponseCurve::Linear))
+        .add_systems(FixedUpdate, calm_down_system
+            .run_if(every_ticks(64))
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// How frightening one creature is to another, 0..1: hunters that are aggressive and big
+/// for their prey score highest, and aggressive prey scare less easily.
+pub fn predator_threat(predator: &Genome, prey: Option<&Genome>) -> f32 {
+    let (size, aggression) = prey.map_or((1.0, 0.0), |g| (g.size, g.aggression));
+    let predatory = predator.diet_type * predator.aggression;
+    let outsized = (predator.size / size.max(0.1) / 2.0).min(1.0);
+    predatory * outsized * (1.0 - aggression * 0.5)
+}
+
+/// The threats a unit knows of: the source of any danger it is in, and other kinds of creature
+/// it recently saw nearby, scaled by how close and how recent the sighting was. Also returns
+/// where the unit's own kind were last seen, to run to, and every other kind it remembers.
+#[allow(clippy::too_many_arguments)]
+pub fn assess(
+    memory: &[Memory],
+    entity: Entity,
+    position: &Position,
+    genome: Option<&Genome>,
+    body: &PhysicalBody,
+    actor_type: Option<&ActorType>,
+    units: &Query<(&Genome, &ActorType)>,
+    now: u64,
+) -> (Vec<Threat>, Vec<Position>, Vec<(Entity, Position)>) {
+    let range = genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32).max(1);
+    let mut threats = Vec::new();
+    let mut allies = Vec::new();
+    let mut strangers = Vec::new();
+    for memory in memory.iter() {
+        let Memory::Seen { entity: other, position: seen_at, tick, .. } = memory else { continue };
+        if *other == entity { continue; }
+        let Ok((other_genome, other_type)) = units.get(*other) else { continue };
+        if Some(other_type) == actor_type {
+            allies.push(*seen_at);
+            continue;
+        }
+        strangers.push((*other, *seen_at));
+        let age = now.saturating_sub(*tick);
+        if age > THREAT_MEMORY { continue; }
+        let proximity = 1.0 - position.distance(seen_at) as f32 / (range + 1) as f32;
+        let recency = 1.0 - age as f32 / THREAT_MEMORY as f32;
+        let level = predator_threat(other_genome, genome) * proximity.max(0.0) * recency;
+        if level > 0.0 {
+            threats.push(Threat { entity: Some(*other), position: *seen_at, level });
+        }
+    }
+    if let Some(danger) = &body.danger {
+        // Where the danger came from, if the unit saw it; otherwise it only knows it is here.
+        let source_at = danger.danger_source.and_then(|source| threats.iter().find(|t| t.entity == Some(source)).map(|t| t.position));
+        threats.push(Threat { entity: danger.danger_source, position: source_at.unwrap_or(*position), level: DANGER_THREAT });
+    }
+    (threats, allies, strangers)
+}
+
+/// The most threatening thing the unit knows of.
+fn worst(threats: &[Threat]) -> Option<&Threat> {
+    threats.iter().max_by(|a, b| a.level.partial_cmp(&b.level).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// How threatened the unit feels: its worst threat.
+pub struct Threatened;
+
+impl Consideration for Threatened {
+    fn name(&self) -> &'static str { "threat" }
+    fn score(&self, ctx: &Context) -> f32 {
+        worst(&ctx.threats).map_or(0.0, |t| t.level)
+    }
+}
+
+/// The unit's inborn willingness to fight rather than run.
+pub struct Boldness;
+
+impl Consideration for Boldness {
+    fn name(&self) -> &'static str { "boldness" }
+    fn score(&self, ctx: &Context) -> f32 {
+        ctx.genome.map_or(0.5, |g| g.boldness)
+    }
+}
+
+/// Runs to its nest or its own kind if that leads away from the threat, otherwise straight away from it.
+fn flee_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let threat = worst(&ctx.threats)?;
+    let from_threat = ctx.position.distance(&threat.position);
+    let group = (!ctx.allies.is_empty()).then(|| {
+        let n = ctx.allies.len() as i32;
+        let (x, y) = ctx.allies.iter().fold((0, 0), |(x, y), p| (x + p.x, y + p.y));
+        Position { x: x / n, y: y / n, z: ctx.position.z }
+    });
+    let refuge = ctx.nest.into_iter().chain(group)
+        .find(|refuge| *refuge != ctx.position && refuge.distance(&threat.position) > from_threat);
+    let destination = refuge.unwrap_or_else(|| {
+        let (dx, dy) = ((ctx.position.x - threat.position.x) as f32, (ctx.position.y - threat.position.y) as f32);
+        let length = (dx * dx + dy * dy).sqrt();
+        // Standing on the threat's last known spot: any direction will do.
+        let (dx, dy) = if length == 0.0 { (1.0, 0.0) } else { (dx / length, dy / length) };
+        Position {
+            x: ctx.position.x + (dx * FLEE_DISTANCE).round() as i32,
+            y: ctx.position.y + (dy * FLEE_DISTANCE).round() as i32,
+            z: ctx.position.z,
+        }
+    });
+    Some(vec![AtomicAction::Move(destination)])
+}
+
+/// Turns on the threat and fights it, if it knows what the threat is.
+fn stand_ground_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let threat = worst(&ctx.threats)?;
+    let attacker = threat.entity?;
+    Some(engage(ctx, attacker, threat.position))
+}
+
+/// A unit stops feeling in danger once the source is gone or out of its sensory range.
+pub fn calm_down_system(
+    mut units: Query<(&mut PhysicalBody, &Position, Option<&Genome>)>,
+    positions: Query<&Position>,
+) {
+    for (mut body, position, genome) in units.iter_mut() {
+        let Some(danger) = &body.danger else { continue };
+        let range = genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32);
+        let near = danger.danger_source
+            .and_then(|source| positions.get(source).ok())
+            .is_some_and(|source_at| position.distance(source_at) <= range);
+        if !near {
+            body.danger = None;
+        }
+    }
+}
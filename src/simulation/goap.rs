@@ -0,0 +1,171 @@
+use crate::prelude::*;
+use crate::simulation::utility::{Context, Known};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+// Force of a strike, and the toughness a bite gets through, at a physical strength of 1.0.
+pub const STRENGTH_SCALE: f32 = 5.0;
+// The planner gives up after looking at this many states.
+const MAX_EXPANSIONS: usize = 2000;
+// Longest plan it will consider.
+const MAX_STEPS: usize = 6;
+
+/// How hard a creature strikes with bare hands, and how tough a thing it can bite through.
+pub fn strength(genome: Option<&Genome>) -> f32 {
+    genome.map_or(1.0, |g| g.physical_strength) * STRENGTH_SCALE
+}
+
+/// What the planner can aim for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+    /// Energy storage topped up by eating something.
+    Fed,
+    /// Carrying a tool.
+    Armed,
+}
+
+impl Goal {
+    fn reached(&self, state: &WorldState) -> bool {
+        match self {
+            Goal::Fed => state.fed,
+            Goal::Armed => state.holding != Holding::Nothing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Holding {
+    Nothing,
+    /// Whatever the unit already carried when planning started.
+    Carried,
+    PickedUp(Entity),
+}
+
+/// The planner's picture of the world: only the facts atomic actions change.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WorldState {
+    at: Position,
+    /// The thing the unit stands next to, after walking to it.
+    near: Option<Entity>,
+    holding: Holding,
+    /// Things the plan has broken, which have turned into their remains.
+    broken: Vec<Entity>,
+    /// Things the plan has eaten or picked up, which are gone from the ground.
+    taken: Vec<Entity>,
+    fed: bool,
+}
+
+/// An atomic action as the planner sees it: applicable in some states, leading to another.
+struct Step {
+    action: AtomicAction,
+    cost: f32,
+    next: WorldState,
+}
+
+/// Cheapest sequence of atomic actions from the unit's current situation to the goal,
+/// using only what it remembers; `None` if it knows of no way.
+pub fn plan(ctx: &Context, goal: Goal) -> Option<Vec<AtomicAction>> {
+    let start = WorldState {
+        at: ctx.position,
+        near: None,
+        holding: if ctx.tool.is_some() { Holding::Carried } else { Holding::Nothing },
+        broken: Vec::new(),
+        taken: Vec::new(),
+        fed: false,
+    };
+    let useful: Vec<&Known> = ctx.known.iter().filter(|k| is_useful(k)).collect();
+
+    // Uniform cost search; costs are kept in thousandths so they can be ordered exactly.
+    let mut states = vec![(start, Vec::new())];
+    let mut frontier = BinaryHeap::new();
+    frontier.push(Reverse((0u32, 0usize)));
+    let mut visited = HashSet::new();
+    while let Some(Reverse((cost, index))) = frontier.pop() {
+        if visited.len() >= MAX_EXPANSIONS { break; }
+        let (state, actions): (WorldState, Vec<AtomicAction>) = states[index].clone();
+        if goal.reached(&state) { return Some(actions); }
+        if actions.len() >= MAX_STEPS || !visited.insert(state.clone()) { continue; }
+
+        for step in steps(ctx, &useful, &state) {
+            let mut next_actions = actions.clone();
+            next_actions.push(step.action);
+            states.push((step.next, next_actions));
+            frontier.push(Reverse((cost + (step.cost * 1000.0) as u32, states.len() - 1)));
+        }
+    }
+    None
+}
+
+/// Only things that can be eaten, struck with, or broken into something edible are worth planning over.
+fn is_useful(known: &Known) -> bool {
+    is_tool(&known.material)
+        || known.material.energy_density > 0.1
+        || known.item.and_then(|i| i.remains()).is_some_and(|r| r.material_properties().energy_density > 0.1)
+}
+
+pub fn is_tool(material: &MaterialProperties) -> bool {
+    material.hardness > 2.0 && material.energy_density < 0.1
+}
+
+/// What a known thing is made of in this state: its remains once the plan has broken it.
+fn material_in(known: &Known, state: &WorldState) -> MaterialProperties {
+    match known.item.and_then(|i| i.remains()) {
+        Some(remains) if state.broken.contains(&known.entity) => remains.material_properties(),
+        _ => known.material.clone(),
+    }
+}
+
+fn strike_force(ctx: &Context, useful: &[&Known], state: &WorldState) -> f32 {
+    let bonus = match state.holding {
+        Holding::Nothing => 0.0,
+        Holding::Carried => ctx.tool.as_ref().map_or(0.0, |t| t.striking_bonus()),
+        Holding::PickedUp(e) => useful.iter().find(|k| k.entity == e).map_or(0.0, |k| k.material.striking_bonus()),
+    };
+    strength(ctx.genome) + bonus
+}
+
+/// Every atomic action that can be taken in `state`, with its cost and outcome.
+/// Move: to any known thing still on the ground.
+/// Link: pick up the tool it stands next to, if empty-handed.
+/// ApplyForce: break what it stands next to, if the strike overcomes its toughness and it leaves remains.
+/// Consume: eat what it stands next to, if it has energy and is soft enough to bite through.
+fn steps(ctx: &Context, useful: &[&Known], state: &WorldState) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for known in useful.iter() {
+        if known.entity == ctx.entity || state.taken.contains(&known.entity) { continue; }
+        if state.near != Some(known.entity) {
+            steps.push(Step {
+                action: AtomicAction::Move(known.position),
+                cost: 1.0 + state.at.distance(&known.position) as f32 * 0.2,
+                next: WorldState { at: known.position, near: Some(known.entity), ..state.clone() },
+            });
+            continue;
+        }
+
+        let material = material_in(known, state);
+        if state.holding == Holding::Nothing && is_tool(&material) {
+            let mut next = state.clone();
+            next.holding = Holding::PickedUp(known.entity);
+            next.taken.push(known.entity);
+            next.near = None;
+            steps.push(Step { action: AtomicAction::Link(ctx.entity, known.entity), cost: 1.0, next });
+        }
+        let remains = known.item.and_then(|i| i.remains());
+        if remains.is_some() && !state.broken.contains(&known.entity) {
+            let force = strength(ctx.genome);
+            if strike_force(ctx, useful, state) - material.hardness > material.toughness {
+                let mut next = state.clone();
+                next.broken.push(known.entity);
+                steps.push(Step { action: AtomicAction::ApplyForce(known.entity, force), cost: 2.0, next });
+            }
+        }
+        if material.energy_density > 0.1 && material.toughness <= strength(ctx.genome) {
+            let mut next = state.clone();
+            next.fed = true;
+            next.taken.push(known.entity);
+            next.near = None;
+            steps.push(Step { action: AtomicAction::Consume(known.entity), cost: 1.0, next });
+        }
+    }
+    steps
+}
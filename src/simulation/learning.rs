@@ -0,0 +1,89 @@
+use crate::prelude::*;
+use crate::simulation::action_system::action_processor_system;
+use crate::simulation::perception::scan_system;
+use crate::simulation::utility::Known;
+
+// A unit keeps at most this many lessons; the least tried are dropped first.
+const MAX_LESSONS: usize = 16;
+// Health counted as much as a full energy store when weighing an outcome.
+const HEALTH_SCALE: f32 = 100.0;
+// How strongly a learned value scales a behavior's score, and the most it can scale it up,
+// or down by its inverse.
+const EXPERIENCE_WEIGHT: f32 = 2.0;
+pub const MAX_EXPERIENCE: f32 = 2.0;
+
+pub struct LearningPlugin;
+
+impl Plugin for LearningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, learning_system
+            .after(action_processor_system)
+            .after(scan_system)
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// The kinds of actions in a plan, and the material of the last thing it acts on.
+pub fn describe(plan: &[AtomicAction], known: &[Known]) -> (Vec<ActionKind>, MaterialFeatures) {
+    let target = plan.iter().rev().find_map(|action| match action {
+        AtomicAction::ApplyForce(e, _) | AtomicAction::Consume(e) | AtomicAction::Link(_, e) | AtomicAction::Attack(e) | AtomicAction::Throw(e) => Some(*e),
+        _ => None,
+    });
+    let features = target
+        .and_then(|e| known.iter().find(|k| k.entity == e))
+        .map(|k| MaterialFeatures::of(&k.material))
+        .unwrap_or_default();
+    (plan.iter().map(ActionKind::from).collect(), features)
+}
+
+/// What a sequence on this kind of material has been worth so far; 0 if never tried.
+pub fn value(lessons: &[Memory], actions: &[ActionKind], target: MaterialFeatures) -> f32 {
+    lessons.iter().find_map(|memory| match memory {
+        Memory::Learned { actions: a, target: t, value, .. } if a == actions && *t == target => Some(*value),
+        _ => None,
+    }).unwrap_or(0.0)
+}
+
+/// Multiplier for a behavior's score from what its plan has been worth before: 1 plus
+/// twice the learned value, so a sequence that gained a tenth of an energy store scores a
+/// fifth higher.
+pub fn experience(lessons: &[Memory], plan: &[AtomicAction], known: &[Known]) -> f32 {
+    let (actions, target) = describe(plan, known);
+    (1.0 + value(lessons, &actions, target) * EXPERIENCE_WEIGHT).clamp(1.0 / MAX_EXPERIENCE, MAX_EXPERIENCE)
+}
+
+/// When a unit's action sequence is over, finished or abandoned, nudges the value it has
+/// learned for that sequence towards how much energy and health it actually gained.
+pub fn learning_system(
+    mut units: Query<(&mut Brain, &PhysicalBody, Option<&Genome>)>,
+) {
+    for (mut brain, body, genome) in units.iter_mut() {
+        if brain.action.is_some() || !brain.action_queue.is_empty() { continue; }
+        let Some(episode) = brain.episode.take() else { continue };
+
+        let outcome = (body.energy_storage - episode.energy) / body.energy_max.max(1.0)
+            + (body.health - episode.health) / HEALTH_SCALE;
+        let rate = genome.map_or(0.3, |g| g.learning_rate);
+
+        let lesson = brain.memory.iter_mut().find(|memory| matches!(memory,
+            Memory::Learned { actions, target, .. } if *actions == episode.actions && *target == episode.target));
+        match lesson {
+            Some(Memory::Learned { value, trials, .. }) => {
+                *value += rate * (outcome - *value);
+                *trials += 1;
+            }
+            _ => brain.memory.push(Memory::Learned { actions: episode.actions, target: episode.target, value: rate * outcome, trials: 1 }),
+        }
+
+        let lessons = brain.memory.iter().filter(|m| matches!(m, Memory::Learned { .. })).count();
+        if lessons > MAX_LESSONS {
+            let least_tried = brain.memory.iter().enumerate()
+                .filter_map(|(i, m)| match m { Memory::Learned { trials, .. } => Some((i, *trials)), _ => None })
+                .min_by_key(|(_, trials)| *trials)
+                .map(|(i, _)| i);
+            if let Some(i) = least_tried {
+                brain.memory.remove(i);
+            }
+        }
+    }
+}
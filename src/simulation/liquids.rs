@@ -0,0 +1,138 @@
+use crate::prelude::*;
+use crate::simulation::pathfinding::is_standable;
+use crate::simulation::clock::every_ticks;
+
+// Water at least this deep can't be waded through; only swimmers cross it.
+pub const DEEP_WATER: f32 = 0.6;
+// Water a spring adds every flow step.
+pub const SPRING_RATE: f32 = 0.15;
+// Share of the depth difference that moves to a lower neighbour each step.
+const FLOW_RATE: f32 = 0.2;
+// Lost from every wet cell each step, so springs can't flood the world forever.
+const EVAPORATION: f32 = 0.001;
+const MIN_DEPTH: f32 = 0.005;
+// Standing water at least this deep can be drunk from.
+const DRINKABLE_DEPTH: f32 = 0.05;
+
+pub struct LiquidPlugin;
+
+impl Plugin for LiquidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+            liquid_flow_system.run_if(every_ticks(16)),
+            humidity_system.run_if(every_ticks(64)),
+        ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// A cell water can move into: a floor it can lie on, or open air it will fall through.
+fn accepts_liquid(tiletypes: &TileHash, position: &Position) -> bool {
+    !tiletypes.hash.contains_key(position) || is_standable(tiletypes, position)
+}
+
+/// Whether a unit next to this position can drink there: a water tile, or a puddle deep enough.
+pub fn is_drinkable(tiletypes: &TileHash, liquids: &LiquidHash, position: &Position) -> bool {
+    tiletypes.hash.get(position) == Some(&TileType::Water) || liquids.depth(position) >= DRINKABLE_DEPTH
+}
+
+/// Whether a unit standing at `position` can drink the water at `water`: next to it on its own
+/// level, or one level down over the edge, like a lake sunk into its basin.
+pub fn within_reach(position: &Position, water: &Position) -> bool {
+    (water.x - position.x).abs() <= 1 && (water.y - position.y).abs() <= 1
+        && (water.z == position.z || water.z == position.z - 1)
+}
+
+/// One step of cellular flow. Water falls into the cell below when nothing holds it up,
+/// levels out with its neighbours, stacks into the air above once a cell is full,
+/// and leaks away off the edges of the world.
+pub fn liquid_flow_system(
+    mut liquids: ResMut<LiquidHash>,
+    tiletypes: Res<TileHash>,
+    world: Res<WorldConfig>,
+) {
+    // Work from a sorted snapshot so the outcome doesn't depend on HashMap order.
+    let mut cells: Vec<(Position, f32)> = liquids.hash.iter().map(|(p, d)| (*p, *d)).collect();
+    cells.sort_by_key(|(p, _)| (p.z, p.y, p.x));
+    let depth = |p: &Position| liquids.hash.get(p).copied().unwrap_or(0.0);
+    let mut next = liquids.hash.clone();
+    let mut transfer = |from: Position, to: Position, amount: f32| {
+        *next.entry(from).or_default() -= amount;
+        *next.entry(to).or_default() += amount;
+    };
+
+    for (position, mut remaining) in cells {
+        let above = Position { z: position.z + 1, ..position };
+        let below = Position { z: position.z - 1, ..position };
+
+        if !tiletypes.hash.contains_key(&position) {
+            // Nothing to lie on: pour into the cell below, as far as it has room.
+            if accepts_liquid(&tiletypes, &below) {
+                let amount = remaining.min((1.0 - depth(&below)).max(0.0));
+                if amount > 0.0 {
+                    transfer(position, below, amount);
+                    remaining -= amount;
+                }
+            }
+        } else if !is_standable(&tiletypes, &position) {
+            // Built over or walled up: the water is pushed up on top.
+            transfer(position, above, remaining);
+            continue;
+        }
+        if remaining <= MIN_DEPTH { continue; }
+
+        if remaining > 1.0 && !tiletypes.hash.contains_key(&above) {
+            transfer(position, above, remaining - 1.0);
+            remaining = 1.0;
+        }
+
+        let lower: Vec<(Position, f32)> = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+            .map(|(dx, dy)| Position { x: position.x + dx, y: position.y + dy, z: position.z })
+            .filter(|q| accepts_liquid(&tiletypes, q))
+            .map(|q| (q, depth(&q)))
+            .filter(|(_, d)| *d < remaining)
+            .collect();
+        for (neighbour, neighbour_depth) in lower {
+            transfer(position, neighbour, (remaining - neighbour_depth) * FLOW_RATE);
+        }
+    }
+
+    for (position, rate) in liquids.springs.iter() {
+        *next.entry(*position).or_default() += rate;
+    }
+    next.retain(|p, d| {
+        *d -= EVAPORATION;
+        *d > MIN_DEPTH && p.z >= world.min_z && (0..world.width).contains(&p.x) && (0..world.length).contains(&p.y)
+    });
+    liquids.hash = next;
+}
+
+/// Pulls every tile's humidity towards how much water is around it, more slowly inside rooms.
+pub fn humidity_system(
+    liquids: Res<LiquidHash>,
+    rooms: Res<RoomHash>,
+    clock: Res<SimClock>,
+    mut tileenvs: ResMut<TileEnvHash>,
+) {
+    // Ground with no water near drifts towards the season's dry humidity.
+    let dry = clock.season().dry_humidity();
+    let mut wetness: HashMap<Position, f32> = HashMap::new();
+    for (position, depth) in liquids.hash.iter() {
+        let depth = depth.min(1.0);
+        for dx in -2..=2i32 {
+            for dy in -2..=2i32 {
+                for dz in -1..=1i32 {
+                    let distance = dx.abs().max(dy.abs()).max(dz.abs());
+                    let near = Position { x: position.x + dx, y: position.y + dy, z: position.z + dz };
+                    let value = depth / (1 + distance) as f32;
+                    let entry = wetness.entry(near).or_default();
+                    *entry = entry.max(value);
+                }
+            }
+        }
+    }
+
+    for (position, env) in tileenvs.hash.iter_mut() {
+        let target = wetness.get(position).copied().unwrap_or(0.0).max(dry);
+        env.humidity += (target - env.humidity) * 0.1 * rooms.exposure(position);
+    }
+}
@@ -8,4 +8,91 @@ pub mod unitgenerator_system;
 pub mod reproduction_system;
 pub mod action_system;
 pub mod namegiving_system;
+pub mod pathfinding;
+pub mod clock;
+pub mod spatial;
+pub mod perception;
+pub mod utility;
+pub mod goap;
+pub mod learning;
+pub mod liquids;
+pub mod environment;
+pub mod rooms;
+pub mod sleep;
+pub mod fear;
+pub mod combat_system;
+pub mod decomposition;
 
+use crate::prelude::*;
+use crate::initializations;
+
+/// Everything the colony needs to live: world generation, starting spawns and every
+/// simulation system. Has no rendering dependencies, so it runs under `MinimalPlugins` too.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_state::<GameState>()
+        .init_resource::<TileHash>()
+        .init_resource::<TileEnvHash>()
+        .init_resource::<LiquidHash>()
+        .init_resource::<SimRng>()
+        .init_resource::<WorldConfig>()
+        .add_plugins((
+            clock::ClockPlugin,
+            spatial::SpatialPlugin,
+            initializations::BiomePlugin,
+            initializations::StartupPlugin,
+        ))
+        .add_systems(Startup, initializations::generate_map)
+        .add_plugins((
+            monstergenerator_system::MonsterGeneratorPlugin,
+            seasons::SeasonsPlugin,
+            needs::NeedsPlugin,
+            sleep::SleepPlugin,
+            thinking_system::ThinkingPlugin,
+            fear::FearPlugin,
+            perception::PerceptionPlugin,
+            learning::LearningPlugin,
+            action_system::ActionPlugin,
+            pathfinding::PathfindingPlugin,
+            task_system::TaskPlugin,
+            task_system::personality::PersonalityPlugin,
+            reproduction_system::ReproductionPlugin,
+            spoilage_system::SpoilagePlugin,
+        ))
+        .add_plugins((
+            liquids::LiquidPlugin,
+            environment::EnvironmentPlugin,
+            rooms::RoomPlugin,
+            combat_system::CombatPlugin,
+            decomposition::DecompositionPlugin,
+        ))
+        .add_systems(
+            FixedUpdate, (
+                remove_bad_positions,
+                namegiving_system::namegiving_system,
+            ).run_if(in_state(GameState::InGame))
+        );
+    }
+}
+
+/// Despawns whatever ended up inside a wall or off the map. Thrown things in flight cross
+/// open air and are left alone until they land.
+#[allow(clippy::type_complexity)]
+fn remove_bad_positions(
+    mut commands: Commands,
+    query: Query<(Entity, &Position), (Without<MapTile>, Without<Projectile>)>,
+    tiletypes: Res<TileHash>,
+) {
+    for (entity, position) in query.iter() {
+        if tiletypes.hash.contains_key(position) {
+            if tiletypes.hash.get(position).unwrap().is_wall() {
+                commands.entity(entity).despawn();
+            }
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
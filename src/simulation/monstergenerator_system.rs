@@ -1,5 +1,7 @@
 ﻿use crate::prelude::*;
 use crate::simulation::unitgenerator_system::spawn_unit_from_template;
+use crate::simulation::pathfinding::is_standable;
+use std::collections::HashSet;
 
 // Make plugin
 pub struct MonsterGeneratorPlugin;
@@ -13,15 +15,17 @@ impl Plugin for MonsterGeneratorPlugin {
 pub fn monster_generator_system(
     mut commands: Commands,
     entities: Query<(Entity, &Position, &MonsterGenerator)>,
-    tile_types: Query<(&Position, &TileType)>,
-    generated_monsters: Query<(Entity, &GeneratedBy)>,
-    sprite_sheet: Res<SpriteSheet>,
-    mesh_assets: Res<UniversalMeshAssets>,
+    tiletypes: Res<TileHash>,
+    generated_monsters: Query<&GeneratedBy>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
+    let rng = sim_rng.stream("monster_generator");
+    // A generator waits until its last monster is gone.
+    let busy: HashSet<Entity> = generated_monsters.iter().map(|g| g.entity).collect();
     for (entity, position, monster_generator) in entities.iter() {
-        if rand::rng().random_range(0..100) != 0 { continue; }
+        if rng.random_range(0..100) != 0 { continue; }
         let mut new_position = *position;
-        let dir = rand::random::<u32>() % 4;
+        let dir = rng.random_range(0..4);
         match dir {
             0 => new_position.y += 1,
             1 => new_position.y -= 1,
@@ -29,23 +33,10 @@ pub fn monster_generator_system(
             3 => new_position.x += 1,
             _ => {}
         }
-        let mut can_generate = false;
-        for (tile_position, tile_type) in tile_types.iter() {
-            let mut p2 = new_position;
-            p2.z = 0;
-            if *tile_position == p2 && !tile_type.is_wall() {
-                can_generate = true;
-            }
-        }
-        for (_ent, parent) in generated_monsters.iter() {
-            if parent.entity == entity {
-                can_generate = false;
-            }
-        }
-        if !can_generate {
+        if busy.contains(&entity) || !is_standable(&tiletypes, &new_position) {
             continue;
         }
-        let monster = spawn_unit_from_template(&mut commands, new_position, &sprite_sheet, &monster_generator.pick(), &mesh_assets);
+        let monster = spawn_unit_from_template(&mut commands, new_position, &monster_generator.pick(), rng);
         commands.entity(monster).insert(GeneratedBy { entity });
     }
 }
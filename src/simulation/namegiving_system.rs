@@ -3,10 +3,12 @@
 pub fn namegiving_system(
     mut commands: Commands,
     mut query: Query<Entity, With<GiveMeAName>>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
+    let rng = sim_rng.stream("names");
     for entity in query.iter_mut() {
         let names = vec!["Alice","Charlie","Dave","Eve","Frank","Grace","Hank","Iris","Judy","Karl","Linda","Mike","Nancy","Oscar","Peggy","Quinn","Ruth","Steve","Tina","Ursula","Victor","Wendy","Xavier","Yvonne","Zach"];
-        let i = rand::rng().random_range(0..names.len());
+        let i = rng.random_range(0..names.len());
         let text_name = names[i];
 
         commands.entity(entity).insert(HasName { name: text_name.to_string() })
@@ -1,4 +1,13 @@
 ﻿use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+
+// Above this temperature units dry out faster: one extra drain rate for every HEAT_STEP degrees.
+const THIRST_TEMPERATURE: f32 = 20.0;
+const HEAT_STEP: f32 = 10.0;
+// Health lost every needs tick while the water need is empty.
+const DEHYDRATION_DAMAGE: f32 = 1.0;
+// Share of its usual metabolic cost a sleeping unit burns.
+const SLEEP_METABOLISM: f32 = 0.5;
 
 pub struct NeedsPlugin;
 
@@ -6,11 +15,11 @@ impl Plugin for NeedsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(FixedUpdate, (
             needs_status_system
-            .run_if(bevy::time::common_conditions::on_timer(std::time::Duration::from_secs_f32(1.0))),
+            .run_if(every_ticks(64)),
             metabolic_drain_system
-            .run_if(bevy::time::common_conditions::on_timer(std::time::Duration::from_secs_f32(0.5))), // 2Hz metabolic tick
+            .run_if(every_ticks(32)), // metabolic tick, twice a second at 1x
             photosynthesis_system
-            .run_if(bevy::time::common_conditions::on_timer(std::time::Duration::from_secs_f32(0.5))),
+            .run_if(every_ticks(32)),
         ).run_if(in_state(GameState::InGame)));
     }
 }
@@ -18,13 +27,17 @@ impl Plugin for NeedsPlugin {
 pub fn photosynthesis_system(
     mut query: Query<(&Position, &mut PhysicalBody, &Genome)>,
     tile_env: Res<TileEnvHash>,
+    clock: Res<SimClock>,
 ) {
+    // No sun, no photosynthesis.
+    let daylight = clock.daylight();
+    if daylight <= 0.0 { return; }
     for (pos, mut body, genome) in query.iter_mut() {
         let phos_factor = 1.0 - genome.diet_type;
         if phos_factor <= 0.0 { continue; }
 
         if let Some(env) = tile_env.hash.get(pos) {
-            let gain = env.fertility * 0.2 * phos_factor;
+            let gain = env.fertility * 0.2 * phos_factor * daylight;
             body.energy_storage += gain;
             if body.energy_storage > body.energy_max {
                 body.energy_storage = body.energy_max;
@@ -35,9 +48,9 @@ pub fn photosynthesis_system(
 
 pub fn metabolic_drain_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut PhysicalBody, Option<&Genome>)>
+    mut query: Query<(Entity, &mut PhysicalBody, Option<&Genome>, Has<Asleep>)>
 ) {
-    for (entity, mut body, genome) in query.iter_mut() {
+    for (entity, mut body, genome, asleep) in query.iter_mut() {
         // Base cost calculation
         let base_cost = 0.1; // Energy per metabolic tick
         let mut total_cost = base_cost;
@@ -46,15 +59,17 @@ pub fn metabolic_drain_system(
             // Cost scale with Size and Mobility
             total_cost *= g.size.powf(1.5) * 0.7 + g.mobility * 0.3 + 0.1;
         }
+        if asleep {
+            total_cost *= SLEEP_METABOLISM;
+        }
 
         body.energy_storage -= total_cost;
 
-        // Death check
-        if body.energy_storage <= 0.0 {
+        // Death check: starved, or worn down by thirst or wounds
+        if body.energy_storage <= 0.0 || body.health <= 0.0 {
             body.energy_storage = 0.0;
-            // Kill the entity
-            // In the future, we convert it to debris/meat
-            commands.entity(entity).despawn();
+            // Leaves a corpse once the death system gets to it
+            commands.entity(entity).insert(Dying);
         }
         
         // Backwards compatibility sync for UI
@@ -68,9 +83,10 @@ pub fn metabolic_drain_system(
 }
 
 pub fn needs_status_system(
-    mut query: Query<&mut PhysicalBody>
+    mut query: Query<(&mut PhysicalBody, Option<&Position>)>,
+    tile_env: Res<TileEnvHash>,
 ) {
-    for mut physical_body in query.iter_mut() {
+    for (mut physical_body, position) in query.iter_mut() {
         if let Some(needs_food) = physical_body.needs_food.as_mut() {
             needs_food.current -= needs_food.rate;
             if needs_food.current < 0.0 {
@@ -89,6 +105,18 @@ pub fn needs_status_system(
                 needs_sleep.current = 0.0;
             }
         }
+        let temperature = position.and_then(|p| tile_env.hash.get(p)).map_or(THIRST_TEMPERATURE, |env| env.temperature);
+        let mut dehydrated = false;
+        if let Some(needs_water) = physical_body.needs_water.as_mut() {
+            needs_water.current -= needs_water.rate * (1.0 + ((temperature - THIRST_TEMPERATURE) / HEAT_STEP).max(0.0));
+            if needs_water.current < 0.0 {
+                needs_water.current = 0.0;
+            }
+            dehydrated = needs_water.current <= 0.0;
+        }
+        if dehydrated {
+            physical_body.health -= DEHYDRATION_DAMAGE;
+        }
     }
 }
 
@@ -0,0 +1,344 @@
+use crate::prelude::*;
+use crate::simulation::liquids::DEEP_WATER;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+// Upper bound on expanded nodes per search, so an unreachable goal on a big map
+// can't stall a tick.
+const MAX_SEARCH_NODES: usize = 6000;
+
+/// Free-standing objects that might block a tile. Held items (children) don't count.
+pub type ObstacleQuery<'w, 's> = Query<'w, 's, (&'static Position, &'static MaterialProperties), (With<Object>, Without<ChildOf>)>;
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, pathfinding_system
+            .before(crate::simulation::action_system::action_processor_system)
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Fills `Pathing.path` for every entity that has no plan yet, whose next step got blocked,
+/// or whose moving target changed position. Deep water blocks everyone who can't swim.
+pub fn pathfinding_system(
+    mut pathers: Query<(&Position, &mut Pathing, Option<&Targeting>, Option<&Genome>)>,
+    targets: Query<&Position>,
+    objects: ObstacleQuery,
+    tiletypes: Res<TileHash>,
+    liquids: Res<LiquidHash>,
+) {
+    let swimmer_obstacles = obstacle_positions(&objects);
+    let mut walker_obstacles = swimmer_obstacles.clone();
+    walker_obstacles.extend(liquids.hash.iter()
+        .filter(|(_, depth)| **depth >= DEEP_WATER)
+        .map(|(position, _)| *position));
+    for (position, mut pathing, targeting, genome) in pathers.iter_mut() {
+        let obstacles = if genome.is_some_and(|g| g.can_swim()) { &swimmer_obstacles } else { &walker_obstacles };
+        let mut replan = pathing.path.is_empty() && !pathing.unreachable;
+
+        if pathing.moving_target {
+            if let Some(target_position) = targeting.and_then(|t| targets.get(t.target).ok()) {
+                if *target_position != pathing.destination {
+                    pathing.destination = *target_position;
+                    replan = true;
+                }
+            }
+        }
+        if let Some(next) = pathing.path.first() {
+            if !is_passable(&tiletypes, obstacles, next) && *next != pathing.destination {
+                replan = true;
+            }
+        }
+        if !replan { continue; }
+
+        match find_path(&tiletypes, obstacles, position, &pathing.destination) {
+            Some(path) => {
+                pathing.path = path;
+                pathing.unreachable = false;
+            }
+            None => {
+                pathing.path.clear();
+                pathing.unreachable = true;
+            }
+        }
+    }
+}
+
+pub fn obstacle_positions(
+    objects: &ObstacleQuery,
+) -> HashSet<Position> {
+    objects.iter()
+        .filter(|(_, material)| material.is_obstacle())
+        .map(|(position, _)| *position)
+        .collect()
+}
+
+/// A unit can stand on a tile if it exists, is not an obstacle, and nothing sits on top of it
+/// (other than a connector leading down onto it).
+pub fn is_standable(tiletypes: &TileHash, position: &Position) -> bool {
+    match tiletypes.hash.get(position) {
+        Some(tile) => !tile.is_wall() && tiletypes.hash.get(&above(position)).is_none_or(|t| t.connects_down()),
+        None => false,
+    }
+}
+
+pub fn is_passable(tiletypes: &TileHash, obstacles: &HashSet<Position>, position: &Position) -> bool {
+    is_standable(tiletypes, position) && !obstacles.contains(position)
+}
+
+/// Positions reachable in a single step from `position`. Changing Z level is only possible
+/// through connectors: walking straight up off a ramp, straight down onto one, or climbing
+/// stairs and ladders.
+pub fn neighbours(tiletypes: &TileHash, obstacles: &HashSet<Position>, position: &Position) -> Vec<Position> {
+    let mut result = Vec::with_capacity(10);
+    let Some(current) = tiletypes.hash.get(position) else { return result; };
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if dx == 0 && dy == 0 { continue; }
+            // No squeezing diagonally between two blocked cells.
+            if dx != 0 && dy != 0 {
+                let side_a = Position { x: position.x + dx, ..*position };
+                let side_b = Position { y: position.y + dy, ..*position };
+                if is_blocking(tiletypes, obstacles, &side_a) && is_blocking(tiletypes, obstacles, &side_b) { continue; }
+            }
+            let level = Position { x: position.x + dx, y: position.y + dy, z: position.z };
+            let up = above(&level);
+            let down = below(&level);
+            // Ramps sit at steps along the grid, so they are only climbed head on.
+            let straight = dx == 0 || dy == 0;
+            if is_passable(tiletypes, obstacles, &level) {
+                result.push(level);
+            } else if straight && current.is_ramp() && is_passable(tiletypes, obstacles, &up) {
+                result.push(up);
+            } else if straight && tiletypes.hash.get(&down).is_some_and(|t| t.is_ramp()) && is_passable(tiletypes, obstacles, &down) {
+                result.push(down);
+            }
+        }
+    }
+    let up = above(position);
+    if current.connects_up() && tiletypes.hash.get(&up).is_some_and(|t| t.connects_down()) && is_passable(tiletypes, obstacles, &up) {
+        result.push(up);
+    }
+    let down = below(position);
+    if current.connects_down() && tiletypes.hash.get(&down).is_some_and(|t| t.connects_up()) && is_passable(tiletypes, obstacles, &down) {
+        result.push(down);
+    }
+    result
+}
+
+fn above(position: &Position) -> Position {
+    Position { z: position.z + 1, ..*position }
+}
+
+fn below(position: &Position) -> Position {
+    Position { z: position.z - 1, ..*position }
+}
+
+fn is_blocking(tiletypes: &TileHash, obstacles: &HashSet<Position>, position: &Position) -> bool {
+    tiletypes.hash.get(position).is_some_and(|t| t.is_wall()) || obstacles.contains(position)
+}
+
+fn heuristic(a: &Position, b: &Position) -> i32 {
+    (a.x - b.x).abs().max((a.y - b.y).abs()) + (a.z - b.z).abs()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct Node {
+    cost: i32,
+    estimate: i32,
+    position: Position,
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the BinaryHeap pops the lowest estimate first.
+        other.estimate.cmp(&self.estimate).then_with(|| other.cost.cmp(&self.cost))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A* over `TileHash`. Returns the steps to take (excluding `start`), or `None` if there is no route.
+/// If `goal` itself is blocked (a tree, a stone) the path ends next to it instead.
+pub fn find_path(
+    tiletypes: &TileHash,
+    obstacles: &HashSet<Position>,
+    start: &Position,
+    goal: &Position,
+) -> Option<Vec<Position>> {
+    let goal_blocked = !is_passable(tiletypes, obstacles, goal);
+    let is_goal = |p: &Position| {
+        *p == *goal || (goal_blocked && (p.x - goal.x).abs() <= 1 && (p.y - goal.y).abs() <= 1 && (p.z - goal.z).abs() <= 1)
+    };
+    if is_goal(start) { return Some(vec![]); }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Position, Position> = HashMap::new();
+    let mut best_cost: HashMap<Position, i32> = HashMap::new();
+    open.push(Node { cost: 0, estimate: heuristic(start, goal), position: *start });
+    best_cost.insert(*start, 0);
+
+    let mut expanded = 0;
+    while let Some(Node { cost, position, .. }) = open.pop() {
+        if is_goal(&position) {
+            let mut path = vec![position];
+            let mut current = position;
+            while let Some(previous) = came_from.get(&current) {
+                if previous == start { break; }
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if cost > *best_cost.get(&position).unwrap_or(&i32::MAX) { continue; }
+        expanded += 1;
+        if expanded > MAX_SEARCH_NODES { return None; }
+
+        for next in neighbours(tiletypes, obstacles, &position) {
+            let next_cost = cost + 1 + (next.z - position.z).abs();
+            if next_cost < *best_cost.get(&next).unwrap_or(&i32::MAX) {
+                best_cost.insert(next, next_cost);
+                came_from.insert(next, position);
+                open.push(Node { cost: next_cost, estimate: next_cost + heuristic(&next, goal), position: next });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open ground at z 0, `width` by `length`.
+    fn flat(width: i32, length: i32) -> TileHash {
+        let mut tiletypes = TileHash::default();
+        for x in 0..width {
+            for y in 0..length {
+                tiletypes.hash.insert(Position { x, y, z: 0 }, TileType::Grass);
+            }
+        }
+        tiletypes
+    }
+
+    /// Flat ground with a ledge one level up from x 3 on: a cliff unless something connects them.
+    fn ledge() -> TileHash {
+        let mut tiletypes = flat(6, 3);
+        for x in 3..6 {
+            for y in 0..3 {
+                tiletypes.hash.insert(Position { x, y, z: 1 }, TileType::Grass);
+            }
+        }
+        tiletypes
+    }
+
+    fn at(x: i32, y: i32, z: i32) -> Position {
+        Position { x, y, z }
+    }
+
+    #[test]
+    fn a_plain_step_up_or_down_is_not_walkable() {
+        let tiletypes = ledge();
+        let none = HashSet::new();
+        assert!(!neighbours(&tiletypes, &none, &at(2, 1, 0)).contains(&at(3, 1, 1)));
+        assert!(!neighbours(&tiletypes, &none, &at(3, 1, 1)).contains(&at(2, 1, 0)));
+        assert_eq!(find_path(&tiletypes, &none, &at(0, 1, 0), &at(4, 1, 1)), None);
+    }
+
+    #[test]
+    fn ramps_are_climbed_head_on_only() {
+        let mut tiletypes = ledge();
+        tiletypes.hash.insert(at(2, 1, 0), TileType::Ramp);
+        let none = HashSet::new();
+        let up = neighbours(&tiletypes, &none, &at(2, 1, 0));
+        assert!(up.contains(&at(3, 1, 1)));
+        assert!(!up.contains(&at(3, 0, 1)) && !up.contains(&at(3, 2, 1)));
+        assert!(neighbours(&tiletypes, &none, &at(3, 1, 1)).contains(&at(2, 1, 0)));
+        assert!(!neighbours(&tiletypes, &none, &at(3, 0, 1)).contains(&at(2, 1, 0)));
+
+        let path = find_path(&tiletypes, &none, &at(0, 1, 0), &at(4, 1, 1)).unwrap();
+        assert!(path.contains(&at(2, 1, 0)));
+        assert_eq!(path.last(), Some(&at(4, 1, 1)));
+    }
+
+    #[test]
+    fn stairs_connect_only_a_matching_pair() {
+        let mut tiletypes = ledge();
+        let none = HashSet::new();
+        // Stairs up into the ledge, coming out on the stairs down on top of it.
+        tiletypes.hash.insert(at(3, 1, 0), TileType::StairUp);
+        tiletypes.hash.insert(at(3, 1, 1), TileType::StairDown);
+        assert!(neighbours(&tiletypes, &none, &at(3, 1, 0)).contains(&at(3, 1, 1)));
+        assert!(neighbours(&tiletypes, &none, &at(3, 1, 1)).contains(&at(3, 1, 0)));
+        assert!(find_path(&tiletypes, &none, &at(0, 1, 0), &at(5, 1, 1)).is_some());
+
+        // Stairs up into plain ground lead nowhere.
+        tiletypes.hash.insert(at(3, 1, 1), TileType::Grass);
+        assert!(!neighbours(&tiletypes, &none, &at(3, 1, 0)).contains(&at(3, 1, 1)));
+    }
+
+    #[test]
+    fn ladders_are_climbed_both_ways() {
+        let mut tiletypes = ledge();
+        let none = HashSet::new();
+        tiletypes.hash.insert(at(3, 1, 0), TileType::Ladder);
+        tiletypes.hash.insert(at(3, 1, 1), TileType::Ladder);
+        assert!(neighbours(&tiletypes, &none, &at(3, 1, 0)).contains(&at(3, 1, 1)));
+        assert!(neighbours(&tiletypes, &none, &at(3, 1, 1)).contains(&at(3, 1, 0)));
+        let path = find_path(&tiletypes, &none, &at(5, 1, 1), &at(0, 1, 0)).unwrap();
+        assert!(path.contains(&at(3, 1, 0)));
+    }
+
+    #[test]
+    fn blocked_goals_are_reached_from_next_door() {
+        let mut tiletypes = flat(6, 3);
+        let tree: HashSet<Position> = [at(4, 1, 0)].into();
+        let path = find_path(&tiletypes, &tree, &at(0, 1, 0), &at(4, 1, 0)).unwrap();
+        assert_eq!(path.last(), Some(&at(3, 1, 0)));
+
+        tiletypes.hash.insert(at(4, 1, 0), TileType::Stone);
+        let path = find_path(&tiletypes, &HashSet::new(), &at(0, 1, 0), &at(4, 1, 0)).unwrap();
+        assert_eq!(path.last(), Some(&at(3, 1, 0)));
+        // Already next to it: nowhere to go.
+        assert_eq!(find_path(&tiletypes, &HashSet::new(), &at(3, 1, 0), &at(4, 1, 0)), Some(vec![]));
+    }
+
+    #[test]
+    fn no_squeezing_between_two_blocked_corners() {
+        let mut tiletypes = flat(3, 3);
+        tiletypes.hash.insert(at(1, 0, 0), TileType::Stone);
+        let none = HashSet::new();
+        // One blocked side still leaves room to pass.
+        assert!(neighbours(&tiletypes, &none, &at(0, 0, 0)).contains(&at(1, 1, 0)));
+
+        let boulder: HashSet<Position> = [at(0, 1, 0)].into();
+        assert!(!neighbours(&tiletypes, &boulder, &at(0, 0, 0)).contains(&at(1, 1, 0)));
+        assert_eq!(find_path(&tiletypes, &boulder, &at(0, 0, 0), &at(2, 2, 0)), None);
+    }
+
+    #[test]
+    fn searches_give_up_after_max_search_nodes() {
+        // A wall down the middle of a wide field, with a single gap at one end. Going round
+        // means searching the whole near side first, which is more than the search allows.
+        let mut tiletypes = flat(121, 150);
+        for y in 0..150 {
+            tiletypes.hash.insert(at(60, y, 0), TileType::Stone);
+        }
+        let none = HashSet::new();
+        tiletypes.hash.insert(at(60, 149, 0), TileType::Grass);
+        const { assert!(60 * 150 > MAX_SEARCH_NODES) };
+        assert_eq!(find_path(&tiletypes, &none, &at(0, 0, 0), &at(120, 0, 0)), None);
+
+        // With the gap close by, the same trip is found.
+        tiletypes.hash.insert(at(60, 149, 0), TileType::Stone);
+        tiletypes.hash.insert(at(60, 1, 0), TileType::Grass);
+        assert!(find_path(&tiletypes, &none, &at(0, 0, 0), &at(120, 0, 0)).is_some());
+    }
+}
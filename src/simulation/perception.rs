@@ -0,0 +1,136 @@
+use crate::prelude::*;
+use crate::simulation::action_system::action_processor_system;
+use crate::simulation::clock::every_ticks;
+use crate::simulation::pathfinding::is_standable;
+use crate::simulation::liquids::is_drinkable;
+
+// How far units without a genome can sense.
+pub const DEFAULT_SENSORY_RANGE: i32 = 15;
+// An idle unit looks around again once its last scan is this many ticks old.
+pub const SCAN_INTERVAL: u64 = 32;
+// How long a memory lasts at an intelligence of 1.0.
+const MEMORY_SPAN: u64 = TICKS_PER_DAY / 2;
+// How many things a unit can keep in mind at an intelligence of 1.0.
+const MEMORY_CAPACITY: f32 = 20.0;
+// How many of the nearest drinking spots a scan notes.
+const WATER_SIGHTINGS: usize = 3;
+
+pub struct PerceptionPlugin;
+
+impl Plugin for PerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+            scan_system.after(action_processor_system),
+            memory_decay_system.run_if(every_ticks(64)),
+        ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Carries out `AtomicAction::Scan`: the unit notes everything within its sensory range that it
+/// has a line of sight to, along with the nearest water it can drink from, and forgets things it can
+/// see are no longer where it remembered them.
+#[allow(clippy::type_complexity)]
+pub fn scan_system(
+    mut scanners: Query<(Entity, &mut Brain, &Position, Option<&Genome>)>,
+    things: Query<(&MaterialProperties, Option<&Object>), (Without<MapTile>, Without<ChildOf>)>,
+    index: Res<SpatialIndex>,
+    tiletypes: Res<TileHash>,
+    liquids: Res<LiquidHash>,
+    clock: Res<SimClock>,
+) {
+    for (entity, mut brain, position, genome) in scanners.iter_mut() {
+        if brain.action != Some(AtomicAction::Scan) { continue; }
+
+        let range = genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32);
+        let mut seen: Vec<(Entity, Position, MaterialProperties, Option<ItemType>)> = index.in_radius(position, range).into_iter()
+            .filter(|(e, p)| *e != entity && line_of_sight(&tiletypes, position, p))
+            .filter_map(|(e, p)| things.get(e).ok().map(|(material, object)| (e, p, material.clone(), object.map(|o| o.itemtype))))
+            .collect();
+        // Nearest first, so they are the ones kept when there is more than fits in memory.
+        seen.sort_by_key(|(_, p, _, _)| position.distance(p));
+        let water = nearest_water(&tiletypes, &liquids, position, range);
+
+        let in_view = |remembered: &Position| position.distance(remembered) <= range && line_of_sight(&tiletypes, position, remembered);
+        brain.memory.retain(|memory| match memory {
+            Memory::Seen { entity, position: remembered, .. } => !in_view(remembered) && seen.iter().all(|(e, _, _, _)| e != entity),
+            Memory::Water { position: remembered, .. } => !in_view(remembered) && !water.contains(remembered),
+            Memory::Learned { .. } => true,
+        });
+        for (e, p, material, item) in seen {
+            brain.memory.push(Memory::Seen { entity: e, position: p, tick: clock.tick, material, item });
+        }
+        for p in water {
+            brain.memory.push(Memory::Water { position: p, tick: clock.tick });
+        }
+        forget(&mut brain.memory, genome, clock.tick);
+
+        brain.last_scan = Some(clock.tick);
+        brain.action = None;
+    }
+}
+
+/// The closest drinkable spots within `range` that the unit can see, nearest first: on its level,
+/// or one level down where a lake lies sunk in its basin.
+fn nearest_water(tiletypes: &TileHash, liquids: &LiquidHash, position: &Position, range: i32) -> Vec<Position> {
+    let mut water: Vec<Position> = [position.z, position.z - 1].into_iter()
+        .flat_map(|z| (-range..=range).flat_map(move |dy| (-range..=range).map(move |dx| Position { x: position.x + dx, y: position.y + dy, z })))
+        .filter(|p| position.distance(p) <= range && is_drinkable(tiletypes, liquids, p))
+        .collect();
+    water.sort_by_key(|p| position.distance(p));
+    water.into_iter()
+        .filter(|p| line_of_sight(tiletypes, position, p))
+        .take(WATER_SIGHTINGS)
+        .collect()
+}
+
+/// Memories fade: older ones are forgotten first, and less intelligent creatures
+/// forget sooner and keep fewer things in mind.
+pub fn memory_decay_system(
+    mut brains: Query<(&mut Brain, Option<&Genome>)>,
+    clock: Res<SimClock>,
+) {
+    for (mut brain, genome) in brains.iter_mut() {
+        forget(&mut brain.memory, genome, clock.tick);
+    }
+}
+
+/// Drops sightings older than the creature's memory span, then the oldest ones beyond its capacity.
+/// Lessons learned are kept; the learning system limits those.
+fn forget(memory: &mut Vec<Memory>, genome: Option<&Genome>, now: u64) {
+    let intelligence = genome.map_or(1.0, |g| g.intelligence());
+    let span = (MEMORY_SPAN as f32 * intelligence) as u64;
+    let capacity = ((MEMORY_CAPACITY * intelligence) as usize).max(1);
+
+    let (mut seen, lessons): (Vec<Memory>, Vec<Memory>) = std::mem::take(memory).into_iter()
+        .partition(|memory| !matches!(memory, Memory::Learned { .. }));
+    seen.retain(|memory| match memory {
+        Memory::Seen { tick, .. } | Memory::Water { tick, .. } => now.saturating_sub(*tick) <= span,
+        Memory::Learned { .. } => true,
+    });
+    if seen.len() > capacity {
+        // Stable, so equally old memories keep the order they were seen in.
+        seen.sort_by_key(|memory| match memory {
+            Memory::Seen { tick, .. } | Memory::Water { tick, .. } => std::cmp::Reverse(*tick),
+            Memory::Learned { .. } => std::cmp::Reverse(0),
+        });
+        seen.truncate(capacity);
+    }
+    *memory = lessons;
+    memory.extend(seen);
+}
+
+/// Whether anything solid lies on the straight line between two positions. Open air and floors
+/// a unit could stand on let sight through; walls and the ground between levels block it.
+pub fn line_of_sight(tiletypes: &TileHash, from: &Position, to: &Position) -> bool {
+    let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+    let steps = dx.abs().max(dy.abs()).max(dz.abs());
+    (1..steps).all(|i| {
+        let t = i as f32 / steps as f32;
+        let point = Position {
+            x: from.x + (dx as f32 * t).round() as i32,
+            y: from.y + (dy as f32 * t).round() as i32,
+            z: from.z + (dz as f32 * t).round() as i32,
+        };
+        !tiletypes.hash.contains_key(&point) || is_standable(tiletypes, &point)
+    })
+}
@@ -1,48 +1,56 @@
 use crate::prelude::*;
+use crate::simulation::pathfinding::is_standable;
+use crate::simulation::clock::every_ticks;
 use rand::Rng;
-use std::time::Duration;
-use bevy::time::common_conditions::on_timer;
+
+// Ticks a unit has to wait after giving birth before it can reproduce again.
+const REPRODUCTION_COOLDOWN: u64 = 6400;
 
 pub struct ReproductionPlugin;
 
 impl Plugin for ReproductionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, reproduction_system.run_if(on_timer(Duration::from_secs(5))));
+        app.add_systems(FixedUpdate, reproduction_system
+            .run_if(every_ticks(320))
+            .run_if(in_state(GameState::InGame)));
     }
 }
 
 pub fn reproduction_system(
     mut commands: Commands,
     mut query: Query<(Entity, &mut PhysicalBody, &Genome, &Generation, &mut ReproductionStatus, &Position, &ActorType)>,
-    sprite_sheet: Res<SpriteSheet>,
-    time: Res<Time>,
+    tiletypes: Res<TileHash>,
+    clock: Res<SimClock>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    let mut rng = rand::rng();
-    let current_time = time.elapsed().as_secs_f32();
+    let rng = sim_rng.stream("reproduction");
 
     for (_entity, mut body, genome, generation, mut status, pos, actor_type) in query.iter_mut() {
         // Only reproduce if energy is high enough (e.g., > 80% of max)
-        if body.energy_storage > status.energy_threshold && current_time - (status.last_reproduction_tick as f32) > 100.0 {
+        if body.energy_storage > status.energy_threshold && clock.tick.saturating_sub(status.last_reproduction_tick) > REPRODUCTION_COOLDOWN {
             
             // Check for overcrowding (simplified: just random chance or neighbor check)
             if rng.random_bool(0.3) {
                 // Costs 50% of energy to reproduce
                 let energy_cost = body.energy_storage * 0.5;
                 body.energy_storage -= energy_cost;
-                status.last_reproduction_tick = current_time as u64;
+                status.last_reproduction_tick = clock.tick;
 
                 // Mutate genome
-                let child_genome = mutate_genome(genome, genome.mutation_rate);
+                let child_genome = mutate_genome(genome, genome.mutation_rate, rng);
                 let child_generation = generation.value + 1;
 
                 // Spawn child nearby
-                let child_pos = Position {
+                let mut child_pos = Position {
                     x: pos.x + rng.random_range(-1..=1),
                     y: pos.y + rng.random_range(-1..=1),
                     z: pos.z,
                 };
+                if !is_standable(&tiletypes, &child_pos) {
+                    child_pos = *pos;
+                }
 
-                spawn_child(&mut commands, child_pos, &sprite_sheet, &child_genome, child_generation, *actor_type, energy_cost);
+                spawn_child(&mut commands, child_pos, &child_genome, child_generation, *actor_type, energy_cost);
                 
                 info!("Generation {} reproduce child of Generation {}", generation.value, child_generation);
             }
@@ -50,8 +58,7 @@ pub fn reproduction_system(
     }
 }
 
-fn mutate_genome(parent: &Genome, rate: f32) -> Genome {
-    let mut rng = rand::rng();
+fn mutate_genome(parent: &Genome, rate: f32, rng: &mut impl Rng) -> Genome {
     let mut child = parent.clone();
 
     let mut mutate = |val: &mut f32, min: f32, max: f32| {
@@ -64,9 +71,13 @@ fn mutate_genome(parent: &Genome, rate: f32) -> Genome {
     mutate(&mut child.size, 0.1, 5.0);
     mutate(&mut child.mobility, 0.1, 5.0);
     mutate(&mut child.sensory_range, 1.0, 50.0);
+    mutate(&mut child.swimming, 0.0, 1.0);
     mutate(&mut child.metabolic_efficiency, 0.1, 0.95);
     mutate(&mut child.diet_type, 0.0, 1.0);
     mutate(&mut child.aggression, 0.0, 1.0);
+    mutate(&mut child.nocturnal, 0.0, 1.0);
+    mutate(&mut child.learning_rate, 0.0, 1.0);
+    mutate(&mut child.boldness, 0.0, 1.0);
     mutate(&mut child.sociality, 0.0, 1.0);
     mutate(&mut child.mutation_rate, 0.01, 0.2);
     mutate(&mut child.weight_hunger, 0.1, 2.0);
@@ -79,7 +90,6 @@ fn mutate_genome(parent: &Genome, rate: f32) -> Genome {
 fn spawn_child(
     commands: &mut Commands,
     position: Position,
-    sprite_sheet: &Res<SpriteSheet>,
     genome: &Genome,
     generation: u32,
     actor_type: ActorType,
@@ -94,15 +104,8 @@ fn spawn_child(
     };
 
     commands.spawn((
-        Sprite {
-            image: sprite_sheet.handle.clone(),
-            texture_atlas: Some(TextureAtlas {
-                layout: sprite_sheet.layout.clone(),
-                index: actor_type.sprite_index(),
-            }),
-            ..default()
-        },
         Transform::from_scale(Vec3::splat(genome.size)).with_translation(position.to_transform().translation),
+        Visibility::default(),
         position,
         genome.clone(),
         Generation { value: generation },
@@ -0,0 +1,123 @@
+use crate::prelude::*;
+use crate::simulation::environment::open_to_sky;
+use crate::simulation::pathfinding::{is_standable, ObstacleQuery};
+use crate::simulation::clock::every_ticks;
+use std::collections::HashSet;
+
+// Regions bigger than this are open country, not rooms.
+const MAX_ROOM_VOLUME: usize = 400;
+// Wall toughness that counts as fully insulating; stone is about 8, dug dirt 0.5.
+const FULL_INSULATION_TOUGHNESS: f32 = 10.0;
+// Even a sealed room lets some of the outside climate in.
+const MAX_INSULATION: f32 = 0.9;
+
+const SIDES: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<RoomHash>()
+        .add_systems(FixedUpdate, room_detection_system
+            .before(crate::simulation::environment::heat_diffusion_system)
+            .before(crate::simulation::liquids::humidity_system)
+            .run_if(every_ticks(128))
+            .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// What is on the other side of a room's edge.
+enum Edge {
+    Floor,
+    Wall(f32),
+    Open,
+}
+
+fn edge(tiletypes: &TileHash, obstacles: &HashMap<Position, f32>, position: &Position) -> Edge {
+    if let Some(toughness) = obstacles.get(position) {
+        return Edge::Wall(*toughness);
+    }
+    if is_standable(tiletypes, position) {
+        return Edge::Floor;
+    }
+    match tiletypes.hash.get(position) {
+        Some(tile) if tile.is_wall() => Edge::Wall(tile.material_properties().toughness),
+        // A floor with a solid block on top: the block is the wall.
+        Some(_) => match tiletypes.hash.get(&Position { z: position.z + 1, ..*position }) {
+            Some(block) => Edge::Wall(block.material_properties().toughness),
+            None => Edge::Open,
+        },
+        // Nothing to stand on: a drop to the outside.
+        None => Edge::Open,
+    }
+}
+
+/// Flood-fills the floor of every level into regions bounded by walls and obstacles,
+/// and keeps the ones small and covered enough to be rooms.
+pub fn room_detection_system(
+    tiletypes: Res<TileHash>,
+    world: Res<WorldConfig>,
+    objects: ObstacleQuery,
+    mut rooms: ResMut<RoomHash>,
+) {
+    let obstacles: HashMap<Position, f32> = objects.iter()
+        .filter(|(_, material)| material.is_obstacle())
+        .map(|(position, material)| (*position, material.toughness))
+        .collect();
+
+    // Sorted so rooms get the same ids on every run.
+    let mut floors: Vec<Position> = tiletypes.hash.keys()
+        .filter(|p| !obstacles.contains_key(p) && is_standable(&tiletypes, p))
+        .copied()
+        .collect();
+    floors.sort_by_key(|p| (p.z, p.y, p.x));
+
+    let mut visited: HashSet<Position> = HashSet::new();
+    let mut found = RoomHash::default();
+    for start in floors {
+        if !visited.insert(start) { continue; }
+        let mut cells = vec![start];
+        let mut frontier = vec![start];
+        let mut open_edges = 0;
+        let mut wall_edges = 0;
+        let mut wall_toughness = 0.0;
+        while let Some(position) = frontier.pop() {
+            for (dx, dy) in SIDES {
+                let neighbour = Position { x: position.x + dx, y: position.y + dy, z: position.z };
+                match edge(&tiletypes, &obstacles, &neighbour) {
+                    Edge::Floor => {
+                        if visited.insert(neighbour) {
+                            cells.push(neighbour);
+                            frontier.push(neighbour);
+                        }
+                    }
+                    Edge::Wall(toughness) => {
+                        wall_edges += 1;
+                        wall_toughness += toughness;
+                    }
+                    Edge::Open => open_edges += 1,
+                }
+            }
+        }
+        if cells.len() > MAX_ROOM_VOLUME { continue; }
+
+        let sky = cells.iter().filter(|p| open_to_sky(&tiletypes, &world, p)).count();
+        let openness = (open_edges + sky) as f32 / (open_edges + wall_edges + cells.len()).max(1) as f32;
+        // Mostly under the open sky: outdoors.
+        if sky * 2 > cells.len() { continue; }
+
+        let walls = if wall_edges > 0 { (wall_toughness / wall_edges as f32 / FULL_INSULATION_TOUGHNESS).min(1.0) } else { 0.0 };
+        let id = found.rooms.len();
+        found.rooms.push(Room {
+            id,
+            volume: cells.len(),
+            openness,
+            insulation: (1.0 - openness) * walls * MAX_INSULATION,
+        });
+        for cell in cells {
+            found.hash.insert(cell, id);
+        }
+    }
+    *rooms = found;
+}
@@ -1,24 +1,56 @@
 ﻿use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+
+// Plants grow once every this many ticks.
+const GROWTH_INTERVAL: u64 = 320;
 
 pub struct SeasonsPlugin;
 
 impl Plugin for SeasonsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate,
-            seasons_system
-            .run_if(bevy::time::common_conditions::on_timer(std::time::Duration::from_secs_f32(5.0)))
-            .run_if(in_state(GameState::InGame))
-        );
+        app.add_systems(FixedUpdate, (
+            seasonal_events_system,
+            seasons_system.run_if(every_ticks(GROWTH_INTERVAL)),
+        ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Fires once whenever the season turns. Winter kills back the plants: every plant loses
+/// half its growth, and the ones left too small die off.
+pub fn seasonal_events_system(
+    mut commands: Commands,
+    clock: Res<SimClock>,
+    mut last_season: Local<Option<Season>>,
+    mut plants: Query<(Entity, &mut Plant, &mut Transform), Without<ChildOf>>,
+) {
+    let season = clock.season();
+    let previous = last_season.replace(season);
+    if previous.is_none_or(|previous| previous == season) { return; }
+    info!("{} begins ({})", season.name(), clock.date());
+
+    if season == Season::Winter {
+        for (entity, mut plant, mut transform) in plants.iter_mut() {
+            plant.growth *= 0.5;
+            if plant.growth < 0.2 {
+                commands.entity(entity).despawn();
+            } else {
+                transform.scale = Vec3::splat(plant.growth);
+            }
+        }
     }
 }
 
+/// Plants grow with the season and the fertility of the ground they stand on.
 pub fn seasons_system(
-    _commands: Commands,
-    mut plants: Query<(&mut Plant, &mut Transform)>,
+    clock: Res<SimClock>,
+    tileenvs: Res<TileEnvHash>,
+    mut plants: Query<(&Position, &mut Plant, &mut Transform)>,
 ) {
-    for (mut plant, mut transform) in plants.iter_mut() {
+    let rate = clock.season().growth_rate();
+    for (position, mut plant, mut transform) in plants.iter_mut() {
         if plant.growth < 1.0 {
-            plant.growth += 0.05;
+            let fertility = tileenvs.hash.get(position).map_or(0.0, |env| env.fertility);
+            plant.growth = (plant.growth + 0.05 * rate * (0.5 + fertility)).min(1.0);
             transform.scale = Vec3::splat(plant.growth);
         }
     }
@@ -0,0 +1,69 @@
+use crate::prelude::*;
+use crate::simulation::action_system::action_processor_system;
+
+// Sleep need restored every tick of rest, on bare ground.
+const SLEEP_RECOVERY: f32 = 0.05;
+// How much faster a unit recovers in a bed, or at its nest.
+const BED_COMFORT: f32 = 2.0;
+const NEST_COMFORT: f32 = 1.5;
+// Share of its energy store below which hunger wakes a sleeper.
+const STARVING: f32 = 0.15;
+
+pub struct SleepPlugin;
+
+impl Plugin for SleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+            set_nest_system,
+            sleep_system.after(action_processor_system),
+        ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Creatures spawned wanting a nest make one where they stand.
+pub fn set_nest_system(
+    mut commands: Commands,
+    query: Query<(Entity, &Position), With<SetNest>>,
+) {
+    for (entity, position) in query.iter() {
+        commands.entity(entity).insert(Nest { position: *position }).remove::<SetNest>();
+    }
+}
+
+/// Carries out `AtomicAction::Rest`: the unit falls asleep and recovers, faster in a bed or
+/// at its nest, until it is rested and its resting window is over. Danger, injury or
+/// starvation wakes it early.
+#[allow(clippy::type_complexity)]
+pub fn sleep_system(
+    mut commands: Commands,
+    mut sleepers: Query<(Entity, &mut Brain, &mut PhysicalBody, &Position, Option<&Genome>, Option<&Nest>, Option<&Asleep>)>,
+    objects: Query<&Object>,
+    index: Res<SpatialIndex>,
+    clock: Res<SimClock>,
+) {
+    let daylight = clock.daylight();
+    for (entity, mut brain, mut body, position, genome, nest, asleep) in sleepers.iter_mut() {
+        if brain.action != Some(AtomicAction::Rest) {
+            if asleep.is_some() { commands.entity(entity).remove::<Asleep>(); }
+            continue;
+        }
+
+        let rested = body.needs_sleep.as_ref().is_none_or(|need| need.current >= need.high);
+        let active = genome.is_none_or(|g| g.activity(daylight) >= 0.5);
+        let starving = body.energy_storage < body.energy_max * STARVING;
+        let hurt = asleep.is_some_and(|a| body.health < a.health);
+        if body.danger.is_some() || hurt || starving || (rested && active) {
+            brain.action = None;
+            if asleep.is_some() { commands.entity(entity).remove::<Asleep>(); }
+            continue;
+        }
+        if asleep.is_none() { commands.entity(entity).insert(Asleep { health: body.health }); }
+
+        let in_bed = index.at(position).into_iter()
+            .any(|e| objects.get(e).is_ok_and(|object| object.itemtype == ItemType::Bed));
+        let comfort = if in_bed { BED_COMFORT } else if nest.is_some_and(|n| n.position == *position) { NEST_COMFORT } else { 1.0 };
+        if let Some(need) = body.needs_sleep.as_mut() {
+            need.current = (need.current + SLEEP_RECOVERY * comfort).min(need.max);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::prelude::*;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<SpatialIndex>()
+        // Removals are dropped from the index the moment they happen, whatever the state and
+        // whether or not a fixed tick runs that frame.
+        .add_observer(unindex_removed)
+        .add_systems(FixedPreUpdate, sync_spatial_index);
+    }
+}
+
+/// Adds entities that got a `Position` and moves the ones whose `Position` changed.
+pub fn sync_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    moved: Query<(Entity, &Position, Has<MapTile>), Changed<Position>>,
+) {
+    for (entity, position, is_tile) in moved.iter() {
+        if is_tile {
+            index.insert_tile(entity, *position);
+        } else {
+            index.insert(entity, *position);
+        }
+    }
+}
+
+/// Drops entities that lost their `Position` or were despawned.
+fn unindex_removed(removed: On<Remove, Position>, mut index: ResMut<SpatialIndex>) {
+    index.remove(removed.entity);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn despawns_leave_the_index_between_fixed_ticks() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(SpatialPlugin);
+        let entity = app.world_mut().spawn(Position { x: 3, y: 4, z: 0 }).id();
+        app.world_mut().run_schedule(FixedPreUpdate);
+        assert_eq!(app.world().resource::<SpatialIndex>().position(entity), Some(Position { x: 3, y: 4, z: 0 }));
+
+        app.world_mut().despawn(entity);
+        // Frames that come too quickly for the fixed timestep to tick.
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<SpatialIndex>().position(entity), None);
+        assert!(app.world().resource::<SpatialIndex>().at(&Position { x: 3, y: 4, z: 0 }).is_empty());
+    }
+}
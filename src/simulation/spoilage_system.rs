@@ -1,4 +1,8 @@
 ﻿use crate::prelude::*;
+use crate::simulation::clock::every_ticks;
+
+// Food spoils by its `spoilage_rate` once every this many ticks.
+const SPOILAGE_INTERVAL: u64 = 128;
 
 // Make Plugin
 pub struct SpoilagePlugin;
@@ -8,7 +12,7 @@ impl Plugin for SpoilagePlugin {
         app
         .add_systems(FixedUpdate,
             spoilage_system
-            .run_if(bevy::time::common_conditions::on_timer(std::time::Duration::from_secs_f32(2.0)))
+            .run_if(every_ticks(SPOILAGE_INTERVAL))
             .run_if(in_state(GameState::InGame))
         )
         ;
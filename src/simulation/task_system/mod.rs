@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub mod personality;
+
 pub struct TaskPlugin;
 
 impl Plugin for TaskPlugin {
@@ -13,7 +15,9 @@ impl Plugin for TaskPlugin {
 /// A bridge system that converts high-level Tasks into the new Atomic Action sequences
 fn task_bridge_system(
     mut query: Query<(Entity, &mut Brain, &Position)>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
+    let rng = sim_rng.stream("tasks");
     for (_entity, mut brain, pos) in query.iter_mut() {
         if brain.task.is_none() || brain.action.is_some() || !brain.action_queue.is_empty() {
             continue;
@@ -22,14 +26,13 @@ fn task_bridge_system(
         let task = brain.task.unwrap();
         match task {
             Task::Maintain => {
-                // For now, just a placeholder action
-                brain.action_queue.push(AtomicAction::Scan); 
+                brain.action_queue.push(AtomicAction::Rest);
                 brain.task = None;
             },
             Task::Social => {
                 brain.action_queue.push(AtomicAction::Move(Position { 
-                    x: pos.x + rand::rng().random_range(-2..3),
-                    y: pos.y + rand::rng().random_range(-2..3), 
+                    x: pos.x + rng.random_range(-2..3),
+                    y: pos.y + rng.random_range(-2..3), 
                     z: pos.z 
                 }));
                 brain.task = None;
@@ -0,0 +1,20 @@
+use crate::prelude::*;
+use crate::simulation::utility::*;
+
+pub mod human;
+pub mod nopersonality;
+pub mod territorial;
+pub mod vicious;
+
+/// Registers every `PersonalityTrait`'s behaviors and biases.
+pub struct PersonalityPlugin;
+
+impl Plugin for PersonalityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .register_personality(human::Human)
+        .register_personality(nopersonality::Creature)
+        .register_personality(territorial::Territorial)
+        .register_personality(vicious::Vicious);
+    }
+}
@@ -1,14 +1,16 @@
-﻿use crate::prelude::*;
+use crate::prelude::*;
+use crate::simulation::utility::*;
 
-pub fn nopersonality(
-    entity: Entity,
-    mut brain: Mut<Brain>,
-    mut physical_body: Mut<PhysicalBody>,
-    position: &Position,
-    _: Option<&Nest>
-) {
-    if brain.task != Some(Task::Personality) { return; }
-    brain.motivation = Some(Motivation::Meander);
-    brain.task = None;
-}
+/// Plain animals: they neither pick up tools nor seek company for its own sake.
+pub struct Creature;
 
+impl Personality for Creature {
+    fn personality(&self) -> PersonalityTrait { PersonalityTrait::Creature }
+    fn name(&self) -> &'static str { "creature" }
+    fn bias(&self, behavior: &Behavior, _ctx: &Context) -> f32 {
+        match behavior.kind {
+            BehaviorKind::FindTool | BehaviorKind::Socialize => 0.0,
+            _ => 1.0,
+        }
+    }
+}
@@ -1,27 +1,67 @@
-﻿use crate::prelude::*;
-
-pub fn territorial(
-    entity: Entity,
-    mut brain: Mut<Brain>,
-    mut physical_body: Mut<PhysicalBody>,
-    _position: &Position,
-    nest: Option<&Nest>,
-    potential_targets: &Vec<(Entity, Position)>
-) {
-    if brain.task != Some(Task::Personality) { return; }
-    if !brain.personality.contains(&PersonalityTrait::Territorial) { return; }
-    if nest.is_none() { return; }
-    let nest = nest.unwrap();
-    // Anything to defend against?
-    for (target_entity, target_position) in potential_targets.iter() {
-        if entity == *target_entity { continue; }
-        if target_position.distance(&nest.position) < 10 {
-            physical_body.danger = Some(Danger { danger_type: DangerType::Attacked, danger_source: Some(*target_entity) });
-            brain.remotivate();
-            break;
+use crate::prelude::*;
+use crate::simulation::combat_system::engage;
+use crate::simulation::utility::*;
+use rand::rngs::StdRng;
+
+// How far around its nest a territorial creature claims.
+const TERRITORY: i32 = 10;
+
+/// Creatures that keep close to their nest and drive off anything that comes near it.
+pub struct Territorial;
+
+impl Personality for Territorial {
+    fn personality(&self) -> PersonalityTrait { PersonalityTrait::Territorial }
+    fn name(&self) -> &'static str { "territorial" }
+    fn behaviors(&self) -> Vec<Behavior> {
+        vec![
+            Behavior::new(BehaviorKind::DefendTerritory, Motivation::Rage, |g| 180.0 * g.map_or(0.5, |g| g.aggression.max(0.2)), defend_plan)
+                .consider(Intruder, ResponseCurve::Linear),
+            Behavior::new(BehaviorKind::ReturnToNest, Motivation::Idle, |_| 20.0, return_plan)
+                .consider(AwayFromNest, ResponseCurve::Linear),
+        ]
+    }
+    fn bias(&self, behavior: &Behavior, _ctx: &Context) -> f32 {
+        match behavior.kind {
+            BehaviorKind::Wander => 0.5,
+            _ => 1.0,
         }
     }
-    brain.motivation = Some(Motivation::Meander);
-    brain.task = None;
 }
 
+/// The stranger closest to the unit's nest, if one is inside its territory.
+fn intruder(ctx: &Context) -> Option<(Entity, Position, i32)> {
+    let nest = ctx.nest?;
+    ctx.strangers.iter()
+        .map(|(e, p)| (*e, *p, nest.distance(p)))
+        .filter(|(_, _, distance)| *distance < TERRITORY)
+        .min_by_key(|(_, _, distance)| *distance)
+}
+
+/// How deep into the territory the closest intruder is, 0..1.
+pub struct Intruder;
+
+impl Consideration for Intruder {
+    fn name(&self) -> &'static str { "intruder" }
+    fn score(&self, ctx: &Context) -> f32 {
+        intruder(ctx).map_or(0.0, |(_, _, distance)| 1.0 - distance as f32 / TERRITORY as f32)
+    }
+}
+
+/// How far outside its territory the unit has strayed, 0..1 at twice the territory's reach.
+pub struct AwayFromNest;
+
+impl Consideration for AwayFromNest {
+    fn name(&self) -> &'static str { "away from nest" }
+    fn score(&self, ctx: &Context) -> f32 {
+        ctx.nest.map_or(0.0, |nest| (ctx.position.distance(&nest) - TERRITORY) as f32 / TERRITORY as f32)
+    }
+}
+
+fn defend_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let (target, position, _) = intruder(ctx)?;
+    Some(engage(ctx, target, position))
+}
+
+fn return_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    Some(vec![AtomicAction::Move(ctx.nest?)])
+}
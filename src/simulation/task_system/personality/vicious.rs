@@ -1,16 +1,47 @@
-﻿use crate::prelude::*;
+use crate::prelude::*;
+use crate::simulation::perception::DEFAULT_SENSORY_RANGE;
+use crate::simulation::combat_system::engage;
+use crate::simulation::utility::*;
+use rand::rngs::StdRng;
 
-pub fn vicious(
-    entity: Entity,
-    mut brain: Mut<Brain>,
-    mut physical_body: Mut<PhysicalBody>,
-    _position: &Position,
-    nest: Option<&Nest>,
-    potential_targets: &Vec<(Entity, Position)>
-) {
-    if brain.task != Some(Task::Personality) { return; }
-    if !brain.personality.contains(&PersonalityTrait::Vicious) { return; }
-    brain.motivation = Some(Motivation::Rage);
-    brain.task = Some(Task::Fight);
+/// Creatures that attack other kinds on sight and rarely back down.
+pub struct Vicious;
+
+impl Personality for Vicious {
+    fn personality(&self) -> PersonalityTrait { PersonalityTrait::Vicious }
+    fn name(&self) -> &'static str { "vicious" }
+    fn behaviors(&self) -> Vec<Behavior> {
+        vec![
+            Behavior::new(BehaviorKind::Attack, Motivation::Rage, |g| 120.0 * g.map_or(0.5, |g| g.aggression), attack_plan)
+                .consider(StrangerNearby, ResponseCurve::Power(2.0)),
+        ]
+    }
+    fn bias(&self, behavior: &Behavior, _ctx: &Context) -> f32 {
+        match behavior.kind {
+            BehaviorKind::Flee => 0.3,
+            BehaviorKind::StandGround => 1.5,
+            _ => 1.0,
+        }
+    }
+}
+
+/// The closest creature of another kind the unit remembers.
+fn nearest_stranger(ctx: &Context) -> Option<(Entity, Position)> {
+    ctx.strangers.iter().copied().min_by_key(|(_, p)| ctx.position.distance(p))
 }
 
+/// How close the nearest stranger is, 1 when adjacent and 0 at the edge of the unit's senses.
+pub struct StrangerNearby;
+
+impl Consideration for StrangerNearby {
+    fn name(&self) -> &'static str { "stranger nearby" }
+    fn score(&self, ctx: &Context) -> f32 {
+        let range = ctx.genome.map_or(DEFAULT_SENSORY_RANGE, |g| g.sensory_range as i32).max(1);
+        nearest_stranger(ctx).map_or(0.0, |(_, p)| 1.0 - ctx.position.distance(&p) as f32 / range as f32)
+    }
+}
+
+fn attack_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let (target, position) = nearest_stranger(ctx)?;
+    Some(engage(ctx, target, position))
+}
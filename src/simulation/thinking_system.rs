@@ -1,21 +1,54 @@
 ﻿use crate::prelude::*;
+use crate::simulation::perception::SCAN_INTERVAL;
+use crate::simulation::utility::*;
+use crate::simulation::goap::{self, Goal};
+use crate::simulation::learning;
+use crate::simulation::fear;
+use crate::simulation::combat_system;
+use crate::simulation::liquids::within_reach;
+use rand::rngs::StdRng;
 
 pub struct ThinkingPlugin;
 
 impl Plugin for ThinkingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, (
+        app
+        .register_behavior(Behavior::new(BehaviorKind::Eat, Motivation::Hunger, |g| 150.0 * g.map_or(1.0, |g| g.weight_hunger), eat_plan)
+            .consider(Hunger, ResponseCurve::Threshold(0.2)))
+        .register_behavior(Behavior::new(BehaviorKind::Drink, Motivation::Thirst, |_| 170.0, drink_plan)
+            .consider(Thirst, ResponseCurve::Linear))
+        .register_behavior(Behavior::new(BehaviorKind::Sleep, Motivation::Tired, |g| 200.0 * g.map_or(1.0, |g| g.weight_fatigue), rest_plan)
+            .consider(Fatigue, ResponseCurve::Linear))
+        // Resting window: diurnal creatures wind down at night, nocturnal ones by day.
+        .register_behavior(Behavior::new(BehaviorKind::Rest, Motivation::Tired, |g| 60.0 * g.map_or(1.0, |g| g.weight_fatigue), rest_plan)
+            .consider(RestingWindow, ResponseCurve::Logistic { midpoint: 0.6, steepness: 12.0 }))
+        .register_behavior(Behavior::new(BehaviorKind::FindTool, Motivation::Bored, |_| 30.0, find_tool_plan)
+            .consider(HoldingTool, ResponseCurve::Inverse))
+        .register_behavior(Behavior::new(BehaviorKind::Socialize, Motivation::Bored, |g| 100.0 * g.map_or(1.0, |g| g.weight_social), socialize_plan)
+            .consider(Boredom, ResponseCurve::Power(2.0)))
+        .register_behavior(Behavior::new(BehaviorKind::Wander, Motivation::Idle, |_| 1.0, wander_plan))
+        .add_systems(FixedUpdate, (
             thinking_system,
             remotivate_system
         ).run_if(in_state(GameState::InGame)));
     }
 }
 
+/// Scores every registered behavior for each idle unit and queues the plan of the best one
+/// that can be carried out.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn thinking_system(
-    mut query: Query<(Entity, &mut Brain, &PhysicalBody, &Position, Option<&Genome>, Option<&Children>)>,
-    targets: Query<(Entity, &Position, &MaterialProperties), Without<Brain>>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Brain, &PhysicalBody, &Position, Option<&Genome>, Option<&Children>, Option<&Nest>, Option<&ActorType>)>,
+    targets: Query<&MaterialProperties, Without<Brain>>,
+    units: Query<(&Genome, &ActorType)>,
+    registry: Res<BehaviorRegistry>,
+    clock: Res<SimClock>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
-    for (entity, mut brain, physical_body, current_pos, genome, children) in query.iter_mut() {
+    let rng = sim_rng.stream("thinking");
+    let daylight = clock.daylight();
+    for (entity, mut brain, physical_body, current_pos, genome, children, nest, actor_type) in query.iter_mut() {
         // If already busy with an action or task, skip
         if brain.action.is_some() || !brain.action_queue.is_empty() { continue; }
         if brain.task.is_some() { continue; }
@@ -24,126 +57,263 @@ pub fn thinking_system(
             brain.task = Some(brain.task_queue.remove(0));
             continue;
         }
-        
-        let mut motivations = Vec::new();
 
-        // Use default weights if no genome (legacy/static entities)
-        let w_hunger = genome.map_or(1.0, |g| g.weight_hunger);
-        let w_fatigue = genome.map_or(1.0, |g| g.weight_fatigue);
-        let w_social = genome.map_or(1.0, |g| g.weight_social);
-
-        // check if has any tool
-        let has_tool = children.map_or(false, |c| !c.is_empty());
-
-        // 1. Hunger Assessment
-        let hunger_score = (1.0 - physical_body.energy_storage / physical_body.energy_max) * 100.0;
-        if physical_body.energy_storage < physical_body.energy_max * 0.8 {
-             motivations.push((Motivation::Hunger, hunger_score * 1.5 * w_hunger));
+        // Look around before deciding, unless the last look was recent.
+        if brain.last_scan.is_none_or(|tick| clock.tick >= tick + SCAN_INTERVAL) {
+            brain.action_queue.push(AtomicAction::Scan);
+            continue;
         }
 
-        // TOOL GATHERING: If no tool, look for one
-        if !has_tool {
-            motivations.push((Motivation::Bored, 30.0)); // Boredom weight used as placeholder for "Tool search"
-        }
+        // Targets come from what the unit remembers seeing, as long as they still exist.
+        let known = brain.memory.iter()
+            .filter_map(|memory| match memory {
+                Memory::Seen { entity, position, material, item, .. } => Some(Known { entity: *entity, position: *position, material: material.clone(), item: *item }),
+                Memory::Learned { .. } | Memory::Water { .. } => None,
+            })
+            .filter(|k| targets.contains(k.entity))
+            .collect();
+        let water = brain.memory.iter()
+            .filter_map(|memory| match memory {
+                Memory::Water { position, .. } => Some(*position),
+                _ => None,
+            })
+            .collect();
+        let (threats, allies, strangers) = fear::assess(&brain.memory, entity, current_pos, genome, physical_body, actor_type, &units, clock.tick);
+        let prey = combat_system::prey(&brain.memory, genome, actor_type, &units);
+        let lessons = brain.memory.iter().filter(|m| matches!(m, Memory::Learned { .. })).cloned().collect();
+        let ctx = Context {
+            entity,
+            position: *current_pos,
+            body: physical_body,
+            genome,
+            known,
+            water,
+            nest: nest.map(|n| n.position),
+            threats,
+            allies,
+            strangers,
+            prey,
+            personality: brain.personality.clone(),
+            tool: children.and_then(|c| c.iter().find_map(|child| targets.get(child).ok())).cloned(),
+            daylight,
+            lessons,
+        };
 
-        // 2. Fatigue (Sleep)
-        if let Some(n) = &physical_body.needs_sleep {
-            let energy_score = (1.0 - n.current / n.max) * 100.0;
-            if n.current < n.low {
-                motivations.push((Motivation::Tired, energy_score * 2.0 * w_fatigue));
+        // Plans are only worked out best score first, each weighed by what it paid off before,
+        // until no behavior further down could win even with the best of experience.
+        let mut scored: Vec<(&Behavior, BehaviorScore)> = registry.behaviors.iter()
+            .filter(|b| b.available_to(&ctx.personality))
+            .map(|b| (b, registry.score(b, &ctx)))
+            .collect();
+        scored.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal));
+        let mut best: Option<(usize, Vec<AtomicAction>)> = None;
+        for i in 0..scored.len() {
+            let best_score = best.as_ref().map_or(0.0, |(j, _)| scored[*j].1.score);
+            let (behavior, score) = &mut scored[i];
+            if score.score <= 0.0 || score.score * learning::MAX_EXPERIENCE <= best_score { break; }
+            let Some(plan) = (behavior.plan)(&ctx, rng) else { continue };
+            let experience = learning::experience(&ctx.lessons, &plan, &ctx.known);
+            score.factors.push(("experience", experience));
+            score.score *= experience;
+            if score.score > best_score {
+                best = Some((i, plan));
             }
         }
+        let chosen = best.map(|(i, plan)| (scored[i].0, plan));
+        scored.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal));
 
-        // 3. Social/Boredom
-        if let Some(n) = &physical_body.needs_entertainment {
-            let boredom_score = (1.0 - n.current / n.max) * 100.0;
-            if n.current < n.low {
-                motivations.push((Motivation::Bored, boredom_score * w_social));
+        match chosen {
+            Some((behavior, plan)) => {
+                debug!("Entity {:?} chose to {}: {:?}", entity, behavior.kind.name(), plan);
+                let (actions, target) = learning::describe(&plan, &ctx.known);
+                brain.episode = Some(Episode { actions, target, energy: physical_body.energy_storage, health: physical_body.health });
+                brain.motivation = Some(behavior.motivation);
+                brain.action_queue.extend(plan);
             }
+            None => brain.motivation = Some(Motivation::Idle),
         }
+        commands.entity(entity).insert(UtilityScores { scores: scored.into_iter().map(|(_, score)| score).collect() });
+    }
+}
 
-        motivations.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-        let best_motivation = motivations.first().map(|(m, _)| *m).unwrap_or(Motivation::Idle);
-        brain.motivation = Some(best_motivation);
-
-        match best_motivation {
-            Motivation::Hunger => {
-                // Look for nearby high energy density items
-                let mut best_target: Option<(Entity, Position)> = None;
-                let mut max_score = -1.0;
-
-                for (t_entity, t_pos, t_material) in targets.iter() {
-                    if t_material.energy_density > 0.1 {
-                        let dist = current_pos.distance(t_pos) as f32;
-                        let score = t_material.energy_density / (dist * 0.1 + 1.0);
-                        if score > max_score {
-                            max_score = score;
-                            best_target = Some((t_entity, *t_pos));
-                        }
-                    }
-                }
-
-                if let Some((target_id, target_pos)) = best_target {
-                    // Generate atomic sequence: Move -> Consume
-                    brain.action_queue.push(AtomicAction::Move(target_pos));
-                    brain.action_queue.push(AtomicAction::Consume(target_id));
-                    info!("Entity {:?} decided to get food at {:?}", entity, target_pos);
-                } else {
-                    // No food found, meander
-                    brain.action_queue.push(AtomicAction::Move(Position { 
-                        x: current_pos.x + rand::rng().random_range(-5..6),
-                        y: current_pos.y + rand::rng().random_range(-5..6),
-                        z: current_pos.z 
-                    }));
-                }
-            },
-            Motivation::Bored if !has_tool => {
-                // Look for tool (hard item without energy)
-                let mut best_tool: Option<(Entity, Position)> = None;
-                let mut max_score = -1.0;
-
-                for (t_entity, t_pos, t_material) in targets.iter() {
-                    if t_material.hardness > 2.0 && t_material.energy_density < 0.1 {
-                        let dist = current_pos.distance(t_pos) as f32;
-                        let score = t_material.hardness / (dist * 0.1 + 1.0);
-                        if score > max_score {
-                            max_score = score;
-                            best_tool = Some((t_entity, *t_pos));
-                        }
-                    }
-                }
-
-                if let Some((target_id, target_pos)) = best_tool {
-                    brain.action_queue.push(AtomicAction::Move(target_pos));
-                    brain.action_queue.push(AtomicAction::Link(entity, target_id));
-                    info!("Entity {:?} decided to pick up tool at {:?}", entity, target_pos);
-                }
-            },
-            Motivation::Idle => {
-                brain.action_queue.push(AtomicAction::Move(Position { 
-                    x: current_pos.x + rand::rng().random_range(-3..4),
-                    y: current_pos.y + rand::rng().random_range(-3..4),
-                    z: current_pos.z 
-                }));
-            },
-            _ => {
-                // Fallback or generic higher-level assignment
-                match best_motivation {
-                    Motivation::Tired => brain.task = Some(Task::Maintain),
-                    Motivation::Bored => brain.task = Some(Task::Social),
-                    _ => brain.task = Some(Task::Idle),
-                }
+/// How empty the unit's energy store is.
+pub struct Hunger;
+
+impl Consideration for Hunger {
+    fn name(&self) -> &'static str { "hunger" }
+    fn score(&self, ctx: &Context) -> f32 {
+        1.0 - ctx.body.energy_storage / ctx.body.energy_max
+    }
+}
+
+/// How much water the unit is short of, once its water need has dropped below its low mark.
+pub struct Thirst;
+
+impl Consideration for Thirst {
+    fn name(&self) -> &'static str { "thirst" }
+    fn score(&self, ctx: &Context) -> f32 {
+        need_deficit(ctx.body.needs_water.as_ref())
+    }
+}
+
+/// How much sleep the unit is short of, once its sleep need has dropped below its low mark.
+pub struct Fatigue;
+
+impl Consideration for Fatigue {
+    fn name(&self) -> &'static str { "fatigue" }
+    fn score(&self, ctx: &Context) -> f32 {
+        need_deficit(ctx.body.needs_sleep.as_ref())
+    }
+}
+
+/// How bored the unit is, once its entertainment need has dropped below its low mark.
+pub struct Boredom;
+
+impl Consideration for Boredom {
+    fn name(&self) -> &'static str { "boredom" }
+    fn score(&self, ctx: &Context) -> f32 {
+        need_deficit(ctx.body.needs_entertainment.as_ref())
+    }
+}
+
+fn need_deficit(need: Option<&Need>) -> f32 {
+    match need {
+        Some(n) if n.current < n.low => 1.0 - n.current / n.max,
+        _ => 0.0,
+    }
+}
+
+/// How far into its resting window the unit is: the inverse of its activity at this light.
+pub struct RestingWindow;
+
+impl Consideration for RestingWindow {
+    fn name(&self) -> &'static str { "resting window" }
+    fn score(&self, ctx: &Context) -> f32 {
+        1.0 - ctx.genome.map_or(1.0, |g| g.activity(ctx.daylight))
+    }
+}
+
+/// 1 while the unit carries something.
+pub struct HoldingTool;
+
+impl Consideration for HoldingTool {
+    fn name(&self) -> &'static str { "holding tool" }
+    fn score(&self, ctx: &Context) -> f32 {
+        if ctx.tool.is_some() { 1.0 } else { 0.0 }
+    }
+}
+
+/// A random spot on the same level, up to `reach` tiles away in each direction.
+fn random_step(position: &Position, rng: &mut StdRng, reach: i32) -> Position {
+    Position {
+        x: position.x + rng.random_range(-reach..reach + 1),
+        y: position.y + rng.random_range(-reach..reach + 1),
+        z: position.z,
+    }
+}
+
+fn eat_plan(ctx: &Context, rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    // No food known, meander
+    goap::plan(ctx, Goal::Fed).or_else(|| Some(vec![AtomicAction::Move(random_step(&ctx.position, rng, 5))]))
+}
+
+fn drink_plan(ctx: &Context, rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    match ctx.water.iter().min_by_key(|water| ctx.position.distance(water)) {
+        Some(water) => {
+            if within_reach(&ctx.position, water) {
+                Some(vec![AtomicAction::Drink(*water)])
+            } else {
+                Some(vec![AtomicAction::Move(*water), AtomicAction::Drink(*water)])
             }
         }
+        // No water known, meander
+        None => Some(vec![AtomicAction::Move(random_step(&ctx.position, rng, 5))]),
+    }
+}
+
+fn find_tool_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    goap::plan(ctx, Goal::Armed)
+}
+
+/// Sleeps in the nearest bed it knows of, else at its nest, else where it stands.
+fn rest_plan(ctx: &Context, _rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    let bed = ctx.known.iter()
+        .filter(|k| k.item == Some(ItemType::Bed))
+        .min_by_key(|k| ctx.position.distance(&k.position))
+        .map(|k| k.position);
+    match bed.or(ctx.nest) {
+        Some(spot) if spot != ctx.position => Some(vec![AtomicAction::Move(spot), AtomicAction::Rest]),
+        _ => Some(vec![AtomicAction::Rest]),
     }
 }
 
-pub fn remotivate_system(mut query: Query<&mut Brain>) {
+fn socialize_plan(ctx: &Context, rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    Some(vec![AtomicAction::Move(random_step(&ctx.position, rng, 2))])
+}
+
+fn wander_plan(ctx: &Context, rng: &mut StdRng) -> Option<Vec<AtomicAction>> {
+    Some(vec![AtomicAction::Move(random_step(&ctx.position, rng, 3))])
+}
+
+pub fn remotivate_system(
+    mut query: Query<&mut Brain>,
+    mut sim_rng: ResMut<SimRng>,
+) {
+    let rng = sim_rng.stream("remotivate");
     for mut brain in query.iter_mut() {
         // Randomly remotivate
-        if rand::rng().random_bool(0.1) {
+        if rng.random_bool(0.1) {
             brain.remotivate();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::learning::learning_system;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn drink_score(world: &World, unit: Entity) -> &BehaviorScore {
+        world.get::<UtilityScores>(unit).unwrap().scores.iter().find(|s| s.behavior == "Drink").unwrap()
+    }
+
+    #[test]
+    fn a_drink_that_paid_off_raises_the_drink_score() {
+        let mut world = World::new();
+        let mut registry = BehaviorRegistry::default();
+        registry.behaviors.push(Behavior::new(BehaviorKind::Drink, Motivation::Thirst, |_| 170.0, drink_plan)
+            .consider(Thirst, ResponseCurve::Linear));
+        world.insert_resource(registry);
+        world.init_resource::<SimClock>();
+        world.insert_resource(SimRng::new(1));
+
+        let water = Position { x: 1, y: 0, z: 0 };
+        let thirsty = |episode: Option<Episode>| (
+            Position::default(),
+            PhysicalBody {
+                energy_max: 100.0,
+                energy_storage: 40.0,
+                health: 100.0,
+                needs_water: Some(Need { current: 20.0, max: 100.0, low: 30.0, ..default() }),
+                ..default()
+            },
+            // Looked around just now, so it decides straight away.
+            Brain { last_scan: Some(0), memory: vec![Memory::Water { position: water, tick: 0 }], episode, ..default() },
+        );
+        let novice = world.spawn(thirsty(None)).id();
+        // Drank once and came away with 0.4 of its energy store more than before.
+        let (actions, target) = learning::describe(&[AtomicAction::Drink(water)], &[]);
+        let veteran = world.spawn(thirsty(Some(Episode { actions, target, energy: 0.0, health: 100.0 }))).id();
+
+        world.run_system_once(learning_system).unwrap();
+        world.run_system_once(thinking_system).unwrap();
+
+        // Without a genome a unit learns 0.3 of an outcome: a value of 0.12, which scores
+        // 1 + 2 * 0.12 = 1.24 times as high.
+        let (before, after) = (drink_score(&world, novice), drink_score(&world, veteran));
+        assert!(before.factors.contains(&("experience", 1.0)));
+        assert!((after.score / before.score - 1.24).abs() < 1e-4, "{} vs {}", after.score, before.score);
+        assert_eq!(world.get::<Brain>(veteran).unwrap().action_queue, vec![AtomicAction::Drink(water)]);
+    }
+}
@@ -9,14 +9,14 @@ impl Plugin for UnitGeneratorPlugin {
 pub fn spawn_unit_from_template(
     commands: &mut Commands,
     position: Position,
-    sprite_sheet: &Res<SpriteSheet>,
     template: &UnitTemplate,
-    _meshes: &UniversalMeshAssets,
+    rng: &mut impl Rng,
 ) -> Entity {
     let mut physical_body = PhysicalBody {
-        needs_food: template.food_need.map(Into::into),
-        needs_entertainment: template.entertainment_need.map(Into::into),
-        needs_sleep: template.sleep_need.map(Into::into),
+        needs_food: template.food_need.map(|n| n.roll(rng)),
+        needs_entertainment: template.entertainment_need.map(|n| n.roll(rng)),
+        needs_sleep: template.sleep_need.map(|n| n.roll(rng)),
+        needs_water: template.water_need.map(|n| n.roll(rng)),
         energy_max: 100.0,
         energy_storage: 90.0,
         health: 100.0,
@@ -31,15 +31,9 @@ pub fn spawn_unit_from_template(
 
     let entity = commands
         .spawn((
-            Sprite {
-                image: sprite_sheet.handle.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: sprite_sheet.layout.clone(),
-                    index: template.actor_type.sprite_index(),
-                }),
-                ..default()
-            },
             position.to_transform(),
+            Visibility::default(),
+            template.actor_type,
             MaterialProperties {
                 mass: 1.0,
                 hardness: 1.0,
@@ -87,6 +81,7 @@ pub struct UnitTemplate {
     pub food_need: Option<NeedExample>,
     pub entertainment_need: Option<NeedExample>,
     pub sleep_need: Option<NeedExample>,
+    pub water_need: Option<NeedExample>,
     pub personality: Vec<PersonalityTrait>,
     pub skillset: Skillset,
     pub attributes: Attributeset,
@@ -105,80 +100,95 @@ pub struct NeedExample {
     pub variance: f32,
 }
 
-impl From<NeedExample> for Need {
-    fn from(example: NeedExample) -> Self {
-        let mut rng = rand::rng();
-        let v = example.variance;
-        Self {
-            current: example.current + rng.random_range(-v..v),
-            max: example.max + rng.random_range(-v..v),
-            rate: example.rate + rng.random_range(-v..v),
-            low: example.low + rng.random_range(-v..v),
-            normal: example.normal + rng.random_range(-v..v),
-            high: example.high + rng.random_range(-v..v),
+impl NeedExample {
+    /// A concrete `Need` for one individual, each value varied by up to `variance`.
+    pub fn roll(&self, rng: &mut impl Rng) -> Need {
+        let v = self.variance;
+        Need {
+            current: self.current + rng.random_range(-v..v),
+            max: self.max + rng.random_range(-v..v),
+            rate: self.rate + rng.random_range(-v..v),
+            low: self.low + rng.random_range(-v..v),
+            normal: self.normal + rng.random_range(-v..v),
+            high: self.high + rng.random_range(-v..v),
         }
     }
 }
 
 impl UnitTemplate {
-    pub fn human() -> Self {
+    /// The template a creature of this kind is spawned from, e.g. when rebuilding a saved `MonsterGenerator`.
+    pub fn from_actor_type(actor_type: ActorType, rng: &mut impl Rng) -> Self {
+        match actor_type {
+            ActorType::Man | ActorType::Woman => Self::human(rng),
+            ActorType::Elf => Self::elf(rng),
+            ActorType::Dwarf => Self::dwarf(rng),
+            ActorType::Rat => Self::rat(),
+            ActorType::Spider => Self::spider(),
+            ActorType::Crab => Self::crab(),
+            ActorType::Cyclops | ActorType::Monster => Self::cyclops(),
+        }
+    }
+    pub fn human(rng: &mut impl Rng) -> Self {
         Self {
             actor_type: ActorType::Man,
             genome: Some(Genome { 
-                size: 1.0, mobility: 1.0, sensory_range: 15.0, physical_strength: 1.0,
+                size: 1.0, mobility: 1.0, sensory_range: 15.0, physical_strength: 1.0, swimming: 0.6,
                 metabolic_efficiency: 0.8, diet_type: 0.5, thermal_tolerance: 15.0,
-                sociality: 0.8, aggression: 0.2, mutation_rate: 0.05,
+                sociality: 0.8, aggression: 0.2, nocturnal: 0.1, learning_rate: 0.3, boldness: 0.4, mutation_rate: 0.05,
                 weight_hunger: 1.0, weight_fatigue: 1.0, weight_social: 1.0
             }),
             food_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             entertainment_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             sleep_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Human],
-            skillset: Self::random_skillset_humanoid(),
+            skillset: Self::random_skillset_humanoid(rng),
             attributes: Self::random_attributeset_humanoid(),
-            afflictions: Self::random_afflictions_humanoid(),
+            afflictions: Self::random_afflictions_humanoid(rng),
             component_builders: vec![
                 |commands, entity| { commands.entity(entity).insert(GiveMeAName); },
             ],
         }
     }
-    pub fn elf() -> Self {
+    pub fn elf(rng: &mut impl Rng) -> Self {
         Self {
             actor_type: ActorType::Elf,
             genome: Some(Genome { 
-                size: 0.9, mobility: 1.2, sensory_range: 20.0, physical_strength: 1.0,
+                size: 0.9, mobility: 1.2, sensory_range: 20.0, physical_strength: 1.0, swimming: 0.5,
                 metabolic_efficiency: 0.7, diet_type: 0.3, thermal_tolerance: 15.0,
-                sociality: 0.6, aggression: 0.1, mutation_rate: 0.05,
+                sociality: 0.6, aggression: 0.1, nocturnal: 0.2, learning_rate: 0.35, boldness: 0.3, mutation_rate: 0.05,
                 weight_hunger: 0.8, weight_fatigue: 1.2, weight_social: 1.5
             }),
             food_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             entertainment_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             sleep_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Human],
-            skillset: Self::random_skillset_humanoid(),
+            skillset: Self::random_skillset_humanoid(rng),
             attributes: Self::random_attributeset_humanoid(),
-            afflictions: Self::random_afflictions_humanoid(),
+            afflictions: Self::random_afflictions_humanoid(rng),
             component_builders: vec![
                 |commands, entity| { commands.entity(entity).insert(GiveMeAName); },
             ],
         }
     }
-    pub fn dwarf() -> Self {
+    pub fn dwarf(rng: &mut impl Rng) -> Self {
         Self {
             actor_type: ActorType::Dwarf,
             genome: Some(Genome { 
-                size: 0.8, mobility: 0.8, sensory_range: 12.0, physical_strength: 1.5,
+                size: 0.8, mobility: 0.8, sensory_range: 12.0, physical_strength: 1.5, swimming: 0.2,
                 metabolic_efficiency: 0.9, diet_type: 0.6, thermal_tolerance: 20.0,
-                sociality: 0.9, aggression: 0.4, mutation_rate: 0.05,
+                sociality: 0.9, aggression: 0.4, nocturnal: 0.3, learning_rate: 0.25, boldness: 0.7, mutation_rate: 0.05,
                 weight_hunger: 1.5, weight_fatigue: 0.8, weight_social: 0.5
             }),
             food_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             entertainment_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             sleep_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Human],
-            skillset: Self::random_skillset_humanoid(),
+            skillset: Self::random_skillset_humanoid(rng),
             attributes: Self::random_attributeset_humanoid(),
-            afflictions: Self::random_afflictions_humanoid(),
+            afflictions: Self::random_afflictions_humanoid(rng),
             component_builders: vec![
                 |commands, entity| { commands.entity(entity).insert(GiveMeAName); },
             ],
@@ -188,14 +198,15 @@ impl UnitTemplate {
         Self {
             actor_type: ActorType::Rat,
             genome: Some(Genome { 
-                size: 0.3, mobility: 1.5, sensory_range: 8.0, physical_strength: 0.5,
+                size: 0.3, mobility: 1.5, sensory_range: 8.0, physical_strength: 0.5, swimming: 0.7,
                 metabolic_efficiency: 0.5, diet_type: 0.8, thermal_tolerance: 10.0,
-                sociality: 0.4, aggression: 0.1, mutation_rate: 0.1,
+                sociality: 0.4, aggression: 0.1, nocturnal: 0.6, learning_rate: 0.2, boldness: 0.2, mutation_rate: 0.1,
                 weight_hunger: 2.0, weight_fatigue: 1.0, weight_social: 0.1
             }),
             food_need: None,
             entertainment_need: None,
             sleep_need: None,
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Creature, PersonalityTrait::Territorial],
             afflictions: vec![],
             skillset: Skillset::default(),
@@ -210,14 +221,15 @@ impl UnitTemplate {
         Self {
             actor_type: ActorType::Spider,
             genome: Some(Genome { 
-                size: 0.5, mobility: 1.8, sensory_range: 6.0, physical_strength: 0.8,
+                size: 0.5, mobility: 1.8, sensory_range: 6.0, physical_strength: 0.8, swimming: 0.1,
                 metabolic_efficiency: 0.4, diet_type: 1.0, thermal_tolerance: 15.0,
-                sociality: 0.1, aggression: 0.6, mutation_rate: 0.1,
+                sociality: 0.1, aggression: 0.6, nocturnal: 0.4, learning_rate: 0.1, boldness: 0.5, mutation_rate: 0.1,
                 weight_hunger: 1.8, weight_fatigue: 0.5, weight_social: 0.0
             }),
             food_need: None,
             entertainment_need: None,
             sleep_need: None,
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Creature, PersonalityTrait::Territorial],
             afflictions: vec![],
             skillset: Skillset::default(),
@@ -232,14 +244,15 @@ impl UnitTemplate {
         Self {
             actor_type: ActorType::Crab,
             genome: Some(Genome { 
-                size: 0.4, mobility: 0.7, sensory_range: 10.0, physical_strength: 1.2,
+                size: 0.4, mobility: 0.7, sensory_range: 10.0, physical_strength: 1.2, swimming: 1.0,
                 metabolic_efficiency: 0.9, diet_type: 0.4, thermal_tolerance: 5.0,
-                sociality: 0.2, aggression: 0.1, mutation_rate: 0.05,
+                sociality: 0.2, aggression: 0.1, nocturnal: 0.5, learning_rate: 0.1, boldness: 0.3, mutation_rate: 0.05,
                 weight_hunger: 0.5, weight_fatigue: 1.5, weight_social: 0.1
             }),
             food_need: None,
             entertainment_need: None,
             sleep_need: None,
+            water_need: None,
             personality: vec![PersonalityTrait::Creature],
             afflictions: vec![],
             skillset: Skillset::default(),
@@ -255,14 +268,15 @@ impl UnitTemplate {
         Self {
             actor_type: ActorType::Cyclops,
             genome: Some(Genome { 
-                size: 2.5, mobility: 0.6, sensory_range: 15.0, physical_strength: 5.0,
+                size: 2.5, mobility: 0.6, sensory_range: 15.0, physical_strength: 5.0, swimming: 0.3,
                 metabolic_efficiency: 0.6, diet_type: 0.9, thermal_tolerance: 25.0,
-                sociality: 0.1, aggression: 0.9, mutation_rate: 0.02,
+                sociality: 0.1, aggression: 0.9, nocturnal: 0.3, learning_rate: 0.15, boldness: 0.9, mutation_rate: 0.02,
                 weight_hunger: 2.5, weight_fatigue: 0.5, weight_social: 0.0
             }),
             food_need: None,
             entertainment_need: None,
             sleep_need: None,
+            water_need: Some(NeedExample { current: 90.0, max: 100.0, rate: 0.1, low: 10.0, normal: 25.0, high: 80.0, variance: 5.0 }),
             personality: vec![PersonalityTrait::Creature, PersonalityTrait::Vicious],
             afflictions: vec![],
             skillset: Skillset::default(),
@@ -274,8 +288,7 @@ impl UnitTemplate {
             ],
         }
     }
-    pub fn random_afflictions_humanoid() -> Vec<Affliction> {
-        let mut rng = rand::rng();
+    pub fn random_afflictions_humanoid(rng: &mut impl Rng) -> Vec<Affliction> {
         let mut afflictions = vec![];
         if rng.random_bool(0.3) {
             afflictions.push(Affliction {
@@ -288,8 +301,7 @@ impl UnitTemplate {
         }
         afflictions
     }
-    pub fn random_skillset_humanoid() -> Skillset {
-        let mut rng = rand::rng();
+    pub fn random_skillset_humanoid(rng: &mut impl Rng) -> Skillset {
         let ranges = [500..700, 500..700, 300..400];
         let mut values: Vec<i32> = ranges.iter().map(|range| rng.random_range(range.clone())).collect();
         Skillset {
@@ -0,0 +1,228 @@
+use crate::prelude::*;
+use rand::rngs::StdRng;
+
+/// What a unit knows when weighing its options: its body and genes, the things it
+/// remembers seeing that still exist, and what it has learned.
+pub struct Context<'a> {
+    pub entity: Entity,
+    pub position: Position,
+    pub body: &'a PhysicalBody,
+    pub genome: Option<&'a Genome>,
+    pub known: Vec<Known>,
+    /// Drinkable water the unit remembers seeing.
+    pub water: Vec<Position>,
+    /// Where the unit's nest is, if it has one.
+    pub nest: Option<Position>,
+    /// What the unit is afraid of right now.
+    pub threats: Vec<Threat>,
+    /// Where it last saw others of its own kind.
+    pub allies: Vec<Position>,
+    /// Other kinds of creature it remembers seeing, and where.
+    pub strangers: Vec<(Entity, Position)>,
+    /// The strangers it could hunt.
+    pub prey: Vec<(Entity, Position)>,
+    /// The unit's personality traits.
+    pub personality: Vec<PersonalityTrait>,
+    /// What the unit is carrying, if anything.
+    pub tool: Option<MaterialProperties>,
+    pub daylight: f32,
+    /// What the unit has learned about how well its action sequences pay off.
+    pub lessons: Vec<Memory>,
+}
+
+/// A remembered thing, as last seen.
+#[derive(Debug, Clone)]
+pub struct Known {
+    pub entity: Entity,
+    pub position: Position,
+    pub material: MaterialProperties,
+    pub item: Option<ItemType>,
+}
+
+/// Something the unit is afraid of: where it was last seen, and how frightening it is, 0..1.
+#[derive(Debug, Clone)]
+pub struct Threat {
+    pub entity: Option<Entity>,
+    pub position: Position,
+    pub level: f32,
+}
+
+/// One input to a behavior's score, read from the context and normalised to 0..1.
+pub trait Consideration: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+    fn score(&self, ctx: &Context) -> f32;
+}
+
+/// Shapes a consideration's 0..1 input into how much it matters, also 0..1.
+#[derive(Debug, Clone, Copy)]
+pub enum ResponseCurve {
+    Linear,
+    /// 1 - x: matters more the lower the input is.
+    Inverse,
+    /// x raised to a power; above 1 only high inputs count for much.
+    Power(f32),
+    /// S-shaped, rising steeply around the midpoint.
+    Logistic { midpoint: f32, steepness: f32 },
+    /// The input itself, but nothing at all until it passes the threshold.
+    Threshold(f32),
+}
+
+impl ResponseCurve {
+    pub fn evaluate(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match *self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Inverse => 1.0 - x,
+            ResponseCurve::Power(exponent) => x.powf(exponent),
+            ResponseCurve::Logistic { midpoint, steepness } => 1.0 / (1.0 + (-steepness * (x - midpoint)).exp()),
+            ResponseCurve::Threshold(threshold) => if x > threshold { x } else { 0.0 },
+        }
+    }
+}
+
+/// Which behavior is which, for personalities to tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BehaviorKind {
+    Eat, Drink, Sleep, Rest, FindTool, Socialize, Wander,
+    Hunt, Flee, StandGround,
+    Attack, DefendTerritory, ReturnToNest,
+}
+
+impl BehaviorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BehaviorKind::Eat => "Eat",
+            BehaviorKind::Drink => "Drink",
+            BehaviorKind::Sleep => "Sleep",
+            BehaviorKind::Rest => "Rest",
+            BehaviorKind::FindTool => "Find tool",
+            BehaviorKind::Socialize => "Socialize",
+            BehaviorKind::Wander => "Wander",
+            BehaviorKind::Hunt => "Hunt",
+            BehaviorKind::Flee => "Flee",
+            BehaviorKind::StandGround => "Stand ground",
+            BehaviorKind::Attack => "Attack",
+            BehaviorKind::DefendTerritory => "Defend territory",
+            BehaviorKind::ReturnToNest => "Return to nest",
+        }
+    }
+}
+
+/// Something a unit can decide to do. Its score is its weight times the product of its
+/// curved considerations; the best scoring behavior with a plan wins.
+pub struct Behavior {
+    pub kind: BehaviorKind,
+    pub motivation: Motivation,
+    /// How much this behavior counts at full strength, usually scaled by a genome weight.
+    pub weight: fn(Option<&Genome>) -> f32,
+    pub considerations: Vec<(Box<dyn Consideration>, ResponseCurve)>,
+    /// The actions to queue, or `None` if the behavior can't be carried out right now.
+    pub plan: fn(&Context, &mut StdRng) -> Option<Vec<AtomicAction>>,
+    /// Only units with this trait consider the behavior; everyone does if `None`.
+    pub personality: Option<PersonalityTrait>,
+}
+
+impl Behavior {
+    pub fn new(kind: BehaviorKind, motivation: Motivation, weight: fn(Option<&Genome>) -> f32, plan: fn(&Context, &mut StdRng) -> Option<Vec<AtomicAction>>) -> Self {
+        Self { kind, motivation, weight, considerations: Vec::new(), plan, personality: None }
+    }
+    pub fn for_personality(mut self, personality: PersonalityTrait) -> Self {
+        self.personality = Some(personality);
+        self
+    }
+    /// Whether a unit with these traits considers this behavior at all.
+    pub fn available_to(&self, personality: &[PersonalityTrait]) -> bool {
+        self.personality.is_none_or(|p| personality.contains(&p))
+    }
+    pub fn consider(mut self, consideration: impl Consideration, curve: ResponseCurve) -> Self {
+        self.considerations.push((Box::new(consideration), curve));
+        self
+    }
+    pub fn score(&self, ctx: &Context) -> BehaviorScore {
+        let factors: Vec<(&'static str, f32)> = self.considerations.iter()
+            .map(|(consideration, curve)| (consideration.name(), curve.evaluate(consideration.score(ctx))))
+            .collect();
+        let score = factors.iter().fold((self.weight)(ctx.genome), |score, (_, factor)| score * factor);
+        BehaviorScore { behavior: self.kind.name(), score, factors }
+    }
+}
+
+/// How one behavior scored for a unit, with each consideration's curved value.
+#[derive(Debug, Clone)]
+pub struct BehaviorScore {
+    pub behavior: &'static str,
+    pub score: f32,
+    pub factors: Vec<(&'static str, f32)>,
+}
+
+/// A personality trait's part in decision making: behaviors only units with the trait
+/// consider, and a bias on how every behavior scores for them.
+pub trait Personality: Send + Sync + 'static {
+    fn personality(&self) -> PersonalityTrait;
+    fn name(&self) -> &'static str;
+    fn behaviors(&self) -> Vec<Behavior> { Vec::new() }
+    /// Multiplier for a behavior's score; 1.0 leaves it alone.
+    fn bias(&self, _behavior: &Behavior, _ctx: &Context) -> f32 { 1.0 }
+}
+
+/// Every behavior units choose between, and the personalities that shape the choice.
+/// Plugins add theirs with `App::register_behavior` and `App::register_personality`.
+#[derive(Resource, Default)]
+pub struct BehaviorRegistry {
+    pub behaviors: Vec<Behavior>,
+    pub personalities: Vec<Box<dyn Personality>>,
+}
+
+impl BehaviorRegistry {
+    /// A behavior's score for a unit, biased by each of its personality traits.
+    pub fn score(&self, behavior: &Behavior, ctx: &Context) -> BehaviorScore {
+        let mut score = behavior.score(ctx);
+        for personality in self.personalities.iter().filter(|p| ctx.personality.contains(&p.personality())) {
+            let bias = personality.bias(behavior, ctx);
+            if bias != 1.0 {
+                score.factors.push((personality.name(), bias));
+                score.score *= bias;
+            }
+        }
+        score
+    }
+}
+
+pub trait RegisterBehavior {
+    fn register_behavior(&mut self, behavior: Behavior) -> &mut Self;
+    fn register_personality(&mut self, personality: impl Personality) -> &mut Self;
+}
+
+impl RegisterBehavior for App {
+    fn register_behavior(&mut self, behavior: Behavior) -> &mut Self {
+        self.init_resource::<BehaviorRegistry>();
+        self.world_mut().resource_mut::<BehaviorRegistry>().behaviors.push(behavior);
+        self
+    }
+    fn register_personality(&mut self, personality: impl Personality) -> &mut Self {
+        self.init_resource::<BehaviorRegistry>();
+        let personality_trait = personality.personality();
+        for behavior in personality.behaviors() {
+            self.register_behavior(behavior.for_personality(personality_trait));
+        }
+        self.world_mut().resource_mut::<BehaviorRegistry>().personalities.push(Box::new(personality));
+        self
+    }
+}
+
+/// The scores from a unit's last decision, best first, for the inspector.
+#[derive(Component, Debug, Clone, Default)]
+pub struct UtilityScores {
+    pub scores: Vec<BehaviorScore>,
+}
+
+impl UtilityScores {
+    pub fn info_panel(&self) -> Vec<String> {
+        let mut info = vec!["-- Utility --".to_string()];
+        for score in self.scores.iter() {
+            let factors: Vec<String> = score.factors.iter().map(|(name, value)| format!("{} {:.2}", name, value)).collect();
+            info.push(format!("{}: {:.1} [{}]", score.behavior, score.score, factors.join(", ")));
+        }
+        info
+    }
+}